@@ -0,0 +1,110 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::int_vec::IntVec;
+use crate::static_kv::get_bit;
+use crate::static_kv::StaticKV;
+
+/// An Aho-Corasick automaton on the trie of a `StaticKV`, to find all keys occurring in a text.
+///
+/// The links are packed arrays indexed by the LOUDS node id:
+/// - `fail[v]` is the node of the longest proper suffix of the path to `v` that is in the trie;
+/// - `output[v]` is the nearest node on the failure chain of `v` that is a key, or 0 if none;
+/// - `depth[v]` is the length of the path to `v`.
+///
+/// Nodes are in breadth first order in LOUDS, thus the links are built in node id order.
+///
+/// A node must be one byte deeper than its parent, thus the trie must be built without tails.
+pub struct AhoCorasick<LBM>
+where LBM: BitmapOps
+{
+    pub kv: StaticKV<LBM>,
+    pub fail: IntVec,
+    pub output: IntVec,
+    pub depth: IntVec,
+}
+
+impl<LBM> AhoCorasick<LBM>
+where LBM: BitmapOps
+{
+    #[allow(dead_code)]
+    pub fn new(kv: StaticKV<LBM>) -> Self {
+        assert!(kv.tails.is_none(), "a trie with tails is not supported");
+
+        let nodes = kv.labels.len() + 1;
+
+        let mut fail = vec![0u64; nodes];
+        let mut output = vec![0u64; nodes];
+        let mut depth = vec![0u64; nodes];
+
+        for v in 1..nodes {
+            // The i-th label leads to node i + 1, and the count of `1` before it is the parent.
+            let c = kv.labels[v - 1];
+            let parent = kv
+                .label_bitmap
+                .count_ones(kv.label_bitmap.select_ith_zero(v as i32 - 1))
+                .0 as usize;
+
+            depth[v] = depth[parent] + 1;
+
+            if parent != 0 {
+                let mut u = fail[parent] as i32;
+                fail[v] = loop {
+                    if let Some(child) = kv.child(u, c) {
+                        break child as u64;
+                    }
+                    if u == 0 {
+                        break 0;
+                    }
+                    u = fail[u as usize] as i32;
+                };
+            }
+
+            let f = fail[v] as usize;
+            output[v] = if f != 0 && get_bit(&kv.leaves, f) != 0 {
+                f as u64
+            } else {
+                output[f]
+            };
+        }
+
+        AhoCorasick {
+            kv,
+            fail: IntVec::from_values(&fail),
+            output: IntVec::from_values(&output),
+            depth: IntVec::from_values(&depth),
+        }
+    }
+
+    /// Returns every occurrence of a non-empty key in `text` as a range `[start, end)`, ordered
+    /// by `end`, then by length, the longest first.
+    #[allow(dead_code)]
+    pub fn find_all(&self, text: &[u8]) -> Vec<(usize, usize)> {
+        let mut res = vec![];
+        let mut state = 0;
+
+        for (i, c) in text.iter().enumerate() {
+            state = loop {
+                if let Some(child) = self.kv.child(state, *c) {
+                    break child;
+                }
+                if state == 0 {
+                    break 0;
+                }
+                state = self.fail.get(state as usize) as i32;
+            };
+
+            let end = i + 1;
+
+            let mut v = state as usize;
+            if v != 0 && get_bit(&self.kv.leaves, v) == 0 {
+                v = self.output.get(v) as usize;
+            }
+
+            while v != 0 {
+                res.push((end - self.depth.get(v) as usize, end));
+                v = self.output.get(v) as usize;
+            }
+        }
+
+        res
+    }
+}
@@ -0,0 +1,52 @@
+use crate::aho_corasick::AhoCorasick;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::StaticKV;
+use crate::testing::lcg;
+use crate::testing::to_vecs;
+
+fn naive_find_all(keys: &[Vec<u8>], text: &[u8]) -> Vec<(usize, usize)> {
+    let mut res = vec![];
+    for end in 1..=text.len() {
+        let mut found = keys
+            .iter()
+            .filter(|k| !k.is_empty() && text[..end].ends_with(k))
+            .map(|k| (end - k.len(), end))
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        res.extend(found);
+    }
+    res
+}
+
+#[test]
+fn test_aho_corasick_example() -> anyhow::Result<()> {
+    let keys = to_vecs(&["he", "hers", "his", "she"]);
+    let ac = AhoCorasick::new(StaticKV::<LabelBitmap>::new(&keys));
+
+    assert_eq!(vec![(1, 4), (2, 4), (2, 6)], ac.find_all(b"ushers"));
+    assert_eq!(Vec::<(usize, usize)>::new(), ac.find_all(b"xyz"));
+    assert_eq!(vec![(0, 3), (4, 6)], ac.find_all(b"his he"));
+
+    Ok(())
+}
+
+#[test]
+fn test_aho_corasick() -> anyhow::Result<()> {
+    let mut rand = lcg(17);
+
+    let mut keys = (0..300)
+        .map(|_| {
+            let len = rand(6);
+            (0..len).map(|_| b'a' + rand(4) as u8).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    let text = (0..3000).map(|_| b'a' + rand(5) as u8).collect::<Vec<_>>();
+
+    let ac = AhoCorasick::new(StaticKV::<LabelBitmap>::new(&keys));
+    assert_eq!(naive_find_all(&keys, &text), ac.find_all(&text));
+
+    Ok(())
+}
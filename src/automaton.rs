@@ -0,0 +1,573 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+/// An automaton over bytes, used to search keys in a trie.
+///
+/// The trie is walked along with the automaton, and a subtree is skipped once the state can not
+/// lead to a match.
+pub trait Automaton {
+    type State: Clone;
+
+    fn start(&self) -> Self::State;
+
+    /// Returns the state after reading byte `b` in state `state`.
+    fn transition(&self, state: &Self::State, b: u8) -> Self::State;
+
+    /// Returns if the input read so far is accepted.
+    fn is_match(&self, state: &Self::State) -> bool;
+
+    /// Returns if any input from `state` can be accepted. A dead state returns `false`.
+    fn can_match(&self, state: &Self::State) -> bool;
+}
+
+/// An error in a glob or regex pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    /// The byte offset in the pattern.
+    pub pos: usize,
+    pub msg: &'static str,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid pattern at {}: {}", self.pos, self.msg)
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// A set of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteSet(pub [u64; 4]);
+
+impl ByteSet {
+    pub fn empty() -> Self {
+        ByteSet([0; 4])
+    }
+
+    pub fn all() -> Self {
+        ByteSet([u64::MAX; 4])
+    }
+
+    pub fn single(b: u8) -> Self {
+        let mut s = Self::empty();
+        s.insert(b);
+        s
+    }
+
+    pub fn insert(&mut self, b: u8) {
+        self.0[(b >> 6) as usize] |= 1 << (b & 63);
+    }
+
+    pub fn contains(&self, b: u8) -> bool {
+        self.0[(b >> 6) as usize] & (1 << (b & 63)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    pub fn negate(&mut self) {
+        for w in self.0.iter_mut() {
+            *w = !*w;
+        }
+    }
+}
+
+/// An instruction of a Thompson NFA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inst {
+    /// Read a byte in the set and go to the next instruction.
+    Byte(ByteSet),
+
+    /// Go to both instructions.
+    Split(usize, usize),
+
+    Jump(usize),
+
+    Match,
+}
+
+/// The state of an `Nfa` that can not lead to a match.
+pub const DEAD: u32 = 0;
+
+/// A transition of a `Dfa` that is not built yet.
+const UNKNOWN: u32 = u32::MAX;
+
+/// A Thompson NFA compiled from a glob or a regex. A pattern always matches an entire key.
+///
+/// It is run as a DFA built lazily: a state is the id of a set of instructions reachable without
+/// reading a byte, and a transition is computed once and then cached. Only the instructions that
+/// can reach `Match` are kept in a set, thus the empty set, `DEAD`, is the only state that can not
+/// match.
+#[derive(Debug, Clone)]
+pub struct Nfa {
+    pub prog: Vec<Inst>,
+
+    /// `live[pc]` is true if instruction `pc` can reach `Match`.
+    pub live: Vec<bool>,
+
+    /// The state before reading any byte.
+    pub start: u32,
+
+    dfa: RefCell<Dfa>,
+}
+
+/// The states and transitions of an `Nfa` built so far.
+#[derive(Debug, Clone)]
+struct Dfa {
+    /// The sorted instructions of every state.
+    sets: Vec<Vec<usize>>,
+    ids: HashMap<Vec<usize>, u32>,
+
+    /// The state after reading byte `b` in state `s` is `next[s * 256 + b]`, or `UNKNOWN`.
+    next: Vec<u32>,
+
+    matches: Vec<bool>,
+
+    /// Buffers reused for every new transition: the instructions of the next state, and the
+    /// mark of the instructions visited for it, which are the ones equal to `mark`.
+    set: Vec<usize>,
+    visited: Vec<u32>,
+    mark: u32,
+}
+
+impl Nfa {
+    /// Build from a program that ends with `Match`.
+    pub fn new(prog: Vec<Inst>) -> Self {
+        let live = live_insts(&prog);
+
+        let mut nfa = Nfa {
+            dfa: RefCell::new(Dfa {
+                sets: vec![],
+                ids: HashMap::new(),
+                next: vec![],
+                matches: vec![],
+                set: vec![],
+                visited: vec![0; prog.len()],
+                mark: 0,
+            }),
+            prog,
+            live,
+            start: DEAD,
+        };
+
+        let dfa = nfa.dfa.get_mut();
+        let dead = dfa.intern(&nfa.prog);
+        debug_assert_eq!(DEAD, dead);
+
+        dfa.mark += 1;
+        dfa.add(&nfa.prog, &nfa.live, 0);
+        nfa.start = dfa.intern(&nfa.prog);
+
+        nfa
+    }
+
+    /// Compile a glob pattern:
+    /// - `*` matches any bytes;
+    /// - `?` matches one byte;
+    /// - `[abc]`, `[a-z]` match one byte in the class, `[!a-z]` or `[^a-z]` one byte not in it;
+    /// - `\` escapes the next byte.
+    #[allow(dead_code)]
+    pub fn glob(pattern: &str) -> Result<Self, PatternError> {
+        let p = pattern.as_bytes();
+        let mut prog = vec![];
+        let mut i = 0;
+
+        while i < p.len() {
+            match p[i] {
+                b'*' => {
+                    // L: split L+1, L+3; L+1: any; L+2: jump L; L+3:
+                    let l = prog.len();
+                    prog.push(Inst::Split(l + 1, l + 3));
+                    prog.push(Inst::Byte(ByteSet::all()));
+                    prog.push(Inst::Jump(l));
+                    i += 1;
+                }
+                b'?' => {
+                    prog.push(Inst::Byte(ByteSet::all()));
+                    i += 1;
+                }
+                b'[' => {
+                    let (set, next) = parse_class(p, i + 1, true)?;
+                    prog.push(Inst::Byte(set));
+                    i = next;
+                }
+                b'\\' => {
+                    let b = *p.get(i + 1).ok_or(PatternError {
+                        pos: i,
+                        msg: "trailing backslash",
+                    })?;
+                    prog.push(Inst::Byte(ByteSet::single(b)));
+                    i += 2;
+                }
+                b => {
+                    prog.push(Inst::Byte(ByteSet::single(b)));
+                    i += 1;
+                }
+            }
+        }
+
+        prog.push(Inst::Match);
+        Ok(Nfa::new(prog))
+    }
+
+    /// Compile a simple regex, which matches an entire key.
+    ///
+    /// It supports literals, `.`, classes `[a-z]` and `[^a-z]`, groups `(...)`, alternation `|`,
+    /// repetitions `*`, `+`, `?`, and `\` to escape the next byte.
+    #[allow(dead_code)]
+    pub fn regex(pattern: &str) -> Result<Self, PatternError> {
+        let mut parser = Parser {
+            p: pattern.as_bytes(),
+            i: 0,
+        };
+
+        let node = parser.alternation()?;
+        if parser.i < parser.p.len() {
+            return Err(PatternError {
+                pos: parser.i,
+                msg: "unmatched ')'",
+            });
+        }
+
+        let mut prog = vec![];
+        emit(&node, &mut prog);
+        prog.push(Inst::Match);
+
+        Ok(Nfa::new(prog))
+    }
+
+    /// Returns the number of states built so far, including `DEAD`.
+    #[allow(dead_code)]
+    pub fn states(&self) -> usize {
+        self.dfa.borrow().sets.len()
+    }
+}
+
+impl Automaton for Nfa {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.start
+    }
+
+    fn transition(&self, state: &u32, b: u8) -> u32 {
+        let mut dfa = self.dfa.borrow_mut();
+
+        let i = *state as usize * 256 + b as usize;
+        if dfa.next[i] != UNKNOWN {
+            return dfa.next[i];
+        }
+
+        dfa.mark += 1;
+        for k in 0..dfa.sets[*state as usize].len() {
+            let pc = dfa.sets[*state as usize][k];
+            if let Inst::Byte(set) = self.prog[pc] {
+                if set.contains(b) {
+                    dfa.add(&self.prog, &self.live, pc + 1);
+                }
+            }
+        }
+
+        let next = dfa.intern(&self.prog);
+        dfa.next[i] = next;
+        next
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        self.dfa.borrow().matches[*state as usize]
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != DEAD
+    }
+}
+
+impl Dfa {
+    /// Add instruction `pc` and all instructions reachable from it without reading a byte to
+    /// `set`, except those that can not reach `Match`.
+    fn add(&mut self, prog: &[Inst], live: &[bool], pc: usize) {
+        if self.visited[pc] == self.mark || !live[pc] {
+            return;
+        }
+        self.visited[pc] = self.mark;
+
+        match prog[pc] {
+            Inst::Split(a, b) => {
+                self.add(prog, live, a);
+                self.add(prog, live, b);
+            }
+            Inst::Jump(a) => self.add(prog, live, a),
+            Inst::Byte(_) | Inst::Match => self.set.push(pc),
+        }
+    }
+
+    /// Returns the id of the state of the instructions in `set`, and clear `set`. A new state is
+    /// created if there is none.
+    fn intern(&mut self, prog: &[Inst]) -> u32 {
+        self.set.sort_unstable();
+
+        let id = match self.ids.get(&self.set[..]) {
+            Some(id) => *id,
+            None => {
+                let id = self.sets.len() as u32;
+
+                self.matches
+                    .push(self.set.iter().any(|pc| prog[*pc] == Inst::Match));
+                self.next.resize(self.next.len() + 256, UNKNOWN);
+                self.ids.insert(self.set.clone(), id);
+                self.sets.push(self.set.clone());
+
+                id
+            }
+        };
+
+        self.set.clear();
+        id
+    }
+}
+
+/// Returns if every instruction can reach `Match`, reading bytes in non-empty sets.
+fn live_insts(prog: &[Inst]) -> Vec<bool> {
+    let mut live = vec![false; prog.len()];
+
+    // A backward jump needs another round.
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for pc in (0..prog.len()).rev() {
+            let l = match prog[pc] {
+                Inst::Match => true,
+                Inst::Byte(set) => !set.is_empty() && live[pc + 1],
+                Inst::Split(a, b) => live[a] || live[b],
+                Inst::Jump(a) => live[a],
+            };
+
+            if l && !live[pc] {
+                live[pc] = true;
+                changed = true;
+            }
+        }
+    }
+
+    live
+}
+
+/// A parsed regex.
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Byte(ByteSet),
+    Concat(Vec<Node>),
+    Alt(Box<Node>, Box<Node>),
+    Star(Box<Node>),
+    Plus(Box<Node>),
+    Quest(Box<Node>),
+}
+
+struct Parser<'a> {
+    p: &'a [u8],
+    i: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn alternation(&mut self) -> Result<Node, PatternError> {
+        let mut node = self.concat()?;
+
+        while self.peek() == Some(b'|') {
+            self.i += 1;
+            let right = self.concat()?;
+            node = Node::Alt(Box::new(node), Box::new(right));
+        }
+
+        Ok(node)
+    }
+
+    fn concat(&mut self) -> Result<Node, PatternError> {
+        let mut nodes = vec![];
+
+        while let Some(c) = self.peek() {
+            if c == b'|' || c == b')' {
+                break;
+            }
+            nodes.push(self.repeat()?);
+        }
+
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn repeat(&mut self) -> Result<Node, PatternError> {
+        let mut node = self.atom()?;
+
+        while let Some(c) = self.peek() {
+            node = match c {
+                b'*' => Node::Star(Box::new(node)),
+                b'+' => Node::Plus(Box::new(node)),
+                b'?' => Node::Quest(Box::new(node)),
+                _ => break,
+            };
+            self.i += 1;
+        }
+
+        Ok(node)
+    }
+
+    fn atom(&mut self) -> Result<Node, PatternError> {
+        let pos = self.i;
+        let c = self.p[pos];
+        self.i += 1;
+
+        let node = match c {
+            b'(' => {
+                let node = self.alternation()?;
+                if self.peek() != Some(b')') {
+                    return Err(PatternError {
+                        pos,
+                        msg: "unclosed '('",
+                    });
+                }
+                self.i += 1;
+                node
+            }
+            b'.' => Node::Byte(ByteSet::all()),
+            b'[' => {
+                let (set, next) = parse_class(self.p, self.i, false)?;
+                self.i = next;
+                Node::Byte(set)
+            }
+            b'\\' => {
+                let b = self.peek().ok_or(PatternError {
+                    pos,
+                    msg: "trailing backslash",
+                })?;
+                self.i += 1;
+                Node::Byte(ByteSet::single(b))
+            }
+            b'*' | b'+' | b'?' => {
+                return Err(PatternError {
+                    pos,
+                    msg: "nothing to repeat",
+                })
+            }
+            b => Node::Byte(ByteSet::single(b)),
+        };
+
+        Ok(node)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.p.get(self.i).copied()
+    }
+}
+
+/// Append the instructions of `node` to `prog`.
+fn emit(node: &Node, prog: &mut Vec<Inst>) {
+    match node {
+        Node::Empty => {}
+        Node::Byte(set) => prog.push(Inst::Byte(*set)),
+        Node::Concat(nodes) => {
+            for n in nodes.iter() {
+                emit(n, prog);
+            }
+        }
+        Node::Alt(a, b) => {
+            // split L1, L2; L1: a; jump L3; L2: b; L3:
+            let split = prog.len();
+            prog.push(Inst::Split(split + 1, 0));
+            emit(a, prog);
+
+            let jump = prog.len();
+            prog.push(Inst::Jump(0));
+
+            prog[split] = Inst::Split(split + 1, prog.len());
+            emit(b, prog);
+            prog[jump] = Inst::Jump(prog.len());
+        }
+        Node::Star(a) => {
+            // L1: split L2, L3; L2: a; jump L1; L3:
+            let split = prog.len();
+            prog.push(Inst::Split(split + 1, 0));
+            emit(a, prog);
+            prog.push(Inst::Jump(split));
+            prog[split] = Inst::Split(split + 1, prog.len());
+        }
+        Node::Plus(a) => {
+            // L1: a; split L1, L3; L3:
+            let start = prog.len();
+            emit(a, prog);
+            prog.push(Inst::Split(start, prog.len() + 1));
+        }
+        Node::Quest(a) => {
+            // split L1, L2; L1: a; L2:
+            let split = prog.len();
+            prog.push(Inst::Split(split + 1, 0));
+            emit(a, prog);
+            prog[split] = Inst::Split(split + 1, prog.len());
+        }
+    }
+}
+
+/// Parse a character class starting at `i`, right after the `[`. It returns the set and the
+/// position after the `]`.
+///
+/// With `glob`, `!` negates the class as well as `^`.
+fn parse_class(p: &[u8], i: usize, glob: bool) -> Result<(ByteSet, usize), PatternError> {
+    let unclosed = PatternError {
+        pos: i - 1,
+        msg: "unclosed '['",
+    };
+
+    let mut set = ByteSet::empty();
+    let mut i = i;
+
+    let negated = matches!(p.get(i), Some(b'^')) || (glob && matches!(p.get(i), Some(b'!')));
+    if negated {
+        i += 1;
+    }
+
+    let first = i;
+
+    loop {
+        let mut c = *p.get(i).ok_or_else(|| unclosed.clone())?;
+
+        // A `]` right after `[` is a literal.
+        if c == b']' && i > first {
+            break;
+        }
+
+        if c == b'\\' {
+            i += 1;
+            c = *p.get(i).ok_or_else(|| unclosed.clone())?;
+        }
+
+        if p.get(i + 1) == Some(&b'-') && p.get(i + 2).map(|x| *x != b']').unwrap_or(false) {
+            let hi = p[i + 2];
+            if hi < c {
+                return Err(PatternError {
+                    pos: i,
+                    msg: "invalid range",
+                });
+            }
+            for b in c..=hi {
+                set.insert(b);
+            }
+            i += 3;
+        } else {
+            set.insert(c);
+            i += 1;
+        }
+    }
+
+    if negated {
+        set.negate();
+    }
+
+    Ok((set, i + 1))
+}
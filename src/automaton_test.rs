@@ -0,0 +1,224 @@
+use crate::automaton::Automaton;
+use crate::automaton::ByteSet;
+use crate::automaton::Inst;
+use crate::automaton::Nfa;
+use crate::automaton::PatternError;
+use crate::automaton::DEAD;
+use crate::static_kv::BuildOptions;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::StaticKV;
+use crate::testing::to_vecs;
+
+/// Run `a` over `input`.
+fn accepts<A: Automaton>(a: &A, input: &str) -> bool {
+    let mut state = a.start();
+    for b in input.bytes() {
+        state = a.transition(&state, b);
+    }
+    a.is_match(&state)
+}
+
+#[test]
+fn test_glob() -> anyhow::Result<()> {
+    let cases = vec![
+        ("", vec![""], vec!["a"]),
+        ("abc", vec!["abc"], vec!["ab", "abcd", "abd"]),
+        ("a*", vec!["a", "abc"], vec!["", "ba"]),
+        ("*c", vec!["c", "abc", "cc"], vec!["ca", ""]),
+        ("a?c", vec!["abc", "a?c"], vec!["ac", "abbc"]),
+        (
+            "user:*:profile",
+            vec!["user:1:profile", "user::profile", "user:a:b:profile"],
+            vec!["user:1:profil", "user:1:profiles"],
+        ),
+        ("[a-c]x", vec!["ax", "cx"], vec!["dx", "x"]),
+        ("[!a-c]x", vec!["dx", "-x"], vec!["ax", "x"]),
+        ("[^a]", vec!["b"], vec!["a"]),
+        ("[]a]", vec!["]", "a"], vec!["b"]),
+        ("[a-]", vec!["a", "-"], vec!["b"]),
+        ("\\*", vec!["*"], vec!["a"]),
+        ("**?", vec!["a", "ab"], vec![""]),
+    ];
+
+    for (pattern, yes, no) in cases {
+        let g = Nfa::glob(pattern)?;
+
+        for x in yes {
+            assert!(accepts(&g, x), "{} should match {:?}", pattern, x);
+        }
+        for x in no {
+            assert!(!accepts(&g, x), "{} should not match {:?}", pattern, x);
+        }
+    }
+
+    assert_eq!(
+        Err(PatternError {
+            pos: 1,
+            msg: "unclosed '['"
+        }),
+        Nfa::glob("a[bc").map(|_| ())
+    );
+    assert!(Nfa::glob("a\\").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_regex() -> anyhow::Result<()> {
+    let cases = vec![
+        ("", vec![""], vec!["a"]),
+        ("abc", vec!["abc"], vec!["ab", "abcd"]),
+        ("a.c", vec!["abc", "a.c"], vec!["ac"]),
+        ("ab*c", vec!["ac", "abbbc"], vec!["abd"]),
+        ("ab+c", vec!["abc", "abbc"], vec!["ac"]),
+        ("ab?c", vec!["ac", "abc"], vec!["abbc"]),
+        ("a|bc|", vec!["a", "bc", ""], vec!["b", "abc"]),
+        ("(ab)*", vec!["", "ab", "abab"], vec!["a", "aba"]),
+        ("(a|b)+c", vec!["ac", "babc"], vec!["c", "abca"]),
+        ("[0-9]+(\\.[0-9]+)?", vec!["1", "12.5"], vec![
+            "1.", ".5", "a",
+        ]),
+        ("[^0-9]*", vec!["", "abc"], vec!["a1"]),
+        ("(a*)*", vec!["", "aaa"], vec!["b"]),
+    ];
+
+    for (pattern, yes, no) in cases {
+        let r = Nfa::regex(pattern)?;
+
+        for x in yes {
+            assert!(accepts(&r, x), "{} should match {:?}", pattern, x);
+        }
+        for x in no {
+            assert!(!accepts(&r, x), "{} should not match {:?}", pattern, x);
+        }
+    }
+
+    for bad in ["(a", "a)", "*a", "a|+", "[a", "a\\", "[z-a]"] {
+        assert!(Nfa::regex(bad).is_err(), "pattern: {}", bad);
+    }
+
+    Ok(())
+}
+
+/// Accepts keys of even length, and can not match beyond `max` bytes.
+struct EvenLen {
+    max: usize,
+}
+
+impl Automaton for EvenLen {
+    type State = usize;
+
+    fn start(&self) -> usize {
+        0
+    }
+
+    fn transition(&self, state: &usize, _b: u8) -> usize {
+        state + 1
+    }
+
+    fn is_match(&self, state: &usize) -> bool {
+        state & 1 == 0
+    }
+
+    fn can_match(&self, state: &usize) -> bool {
+        *state <= self.max
+    }
+}
+
+#[test]
+fn test_kv_search() -> anyhow::Result<()> {
+    let keys = to_vecs(&[
+        "",
+        "user:1:name",
+        "user:1:profile",
+        "user:22:profile",
+        "user:3:profiles",
+        "users",
+        "video:1:profile",
+    ]);
+
+    for tails in [false, true] {
+        let s = StaticKV::<LabelBitmap>::with_options(&keys, &BuildOptions {
+            tails,
+            ..Default::default()
+        });
+
+        let got = s.search(&Nfa::glob("user:*:profile")?);
+        assert_eq!(to_vecs(&["user:1:profile", "user:22:profile"]), got);
+
+        let got = s.search(&Nfa::glob("*")?);
+        assert_eq!(keys, got);
+
+        let got = s.search(&Nfa::regex("user:[0-9]+:(name|profiles)")?);
+        assert_eq!(to_vecs(&["user:1:name", "user:3:profiles"]), got);
+
+        let got = s.search(&Nfa::regex("x.*")?);
+        assert_eq!(Vec::<Vec<u8>>::new(), got);
+
+        // "user:3:profiles" is longer than 14 bytes.
+        let got = s.search(&EvenLen { max: 14 });
+        assert_eq!(to_vecs(&["", "user:1:profile"]), got);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_nfa_empty_byte_set() -> anyhow::Result<()> {
+    // "a" followed by a byte in an empty set: nothing matches.
+    let nfa = Nfa::new(vec![
+        Inst::Byte(ByteSet::single(b'a')),
+        Inst::Byte(ByteSet::empty()),
+        Inst::Match,
+    ]);
+
+    assert_eq!(vec![false, false, true], nfa.live);
+    assert!(!nfa.can_match(&nfa.start()));
+    assert_eq!(DEAD, nfa.transition(&nfa.start(), b'a'));
+
+    // "a" or "b" followed by a byte in an empty set: only "a" can match.
+    let nfa = Nfa::new(vec![
+        Inst::Split(1, 3),
+        Inst::Byte(ByteSet::single(b'a')),
+        Inst::Jump(5),
+        Inst::Byte(ByteSet::single(b'b')),
+        Inst::Byte(ByteSet::empty()),
+        Inst::Match,
+    ]);
+
+    let start = nfa.start();
+    assert!(nfa.can_match(&start));
+    assert!(nfa.can_match(&nfa.transition(&start, b'a')));
+    assert!(nfa.is_match(&nfa.transition(&start, b'a')));
+    assert!(!nfa.can_match(&nfa.transition(&start, b'b')));
+
+    let s = StaticKV::<LabelBitmap>::new(&to_vecs(&["a", "b", "bc"]));
+    assert_eq!(to_vecs(&["a"]), s.search(&nfa));
+
+    Ok(())
+}
+
+#[test]
+fn test_nfa_cached_states() -> anyhow::Result<()> {
+    let nfa = Nfa::glob("user:*:profile")?;
+
+    let keys = (0..1000)
+        .map(|i| format!("user:{}:profile", i).into_bytes())
+        .collect::<Vec<_>>();
+    let mut sorted = keys.clone();
+    sorted.sort();
+
+    let s = StaticKV::<LabelBitmap>::new(&sorted);
+    assert_eq!(sorted, s.search(&nfa));
+
+    // Transitions are cached: the states do not grow with the keys.
+    let states = nfa.states();
+    assert!(states < 20, "states: {}", states);
+
+    for k in keys.iter() {
+        assert!(accepts(&nfa, std::str::from_utf8(k)?));
+    }
+    assert_eq!(states, nfa.states());
+
+    Ok(())
+}
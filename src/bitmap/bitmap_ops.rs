@@ -1,7 +1,5 @@
 /// Bitmap API
 pub trait BitmapOps {
-    fn new(words: Vec<u64>) -> Self;
-
     /// Calculate the rank at index `i`:
     /// returns the count of `1` upto `i`, exclusive, and the value at `i`(0 or 1).
     fn count_ones(&self, i: i32) -> (i32, i32);
@@ -10,9 +8,27 @@ pub trait BitmapOps {
     /// It requires a rank64 index for speeding up and a select32 index
     fn select_ith_one(&self, i: i32) -> i32;
 
+    /// Returns the indexes of the i-th "1" and the (i+1)-th "1".
+    fn select_2(&self, i: i32) -> (i32, i32) {
+        (self.select_ith_one(i), self.select_ith_one(i + 1))
+    }
+
+    /// Returns the index of the i-th "0".
+    fn select_ith_zero(&self, i: i32) -> i32;
+
     fn count_zeros(&self, i: i32) -> (i32, i32) {
         let (a, v) = self.count_ones(i);
 
         (i as i32 - a, v)
     }
+
+    /// Returns the value at `i`(0 or 1).
+    fn get_bit(&self, i: i32) -> i32 {
+        self.count_ones(i).1
+    }
+}
+
+/// Build a bitmap along with its indexes from bitmap words.
+pub trait BuildBitmap: BitmapOps {
+    fn new(words: Vec<u64>) -> Self;
 }
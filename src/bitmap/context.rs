@@ -1,3 +1,4 @@
+use crate::bitmap::excess::ExcessLookup8;
 use crate::bitmap::mask::Masks;
 use crate::bitmap::select::SelectLookup8;
 
@@ -5,6 +6,7 @@ use crate::bitmap::select::SelectLookup8;
 pub struct Context {
     pub masks: Masks,
     pub select_lookup_8: SelectLookup8,
+    pub excess_lookup_8: ExcessLookup8,
 }
 
 impl Context {
@@ -12,6 +14,7 @@ impl Context {
         Context {
             masks: Masks::new(),
             select_lookup_8: SelectLookup8::new(),
+            excess_lookup_8: ExcessLookup8::new(),
         }
     }
 }
@@ -0,0 +1,39 @@
+/// ExcessLookup8 is a lookup table of the excess of an 8-bit bitmap of parentheses, where `1` is
+/// an open parenthesis and `0` is a close parenthesis.
+///
+/// For a byte `b`, with bits in position order from the least significant bit:
+/// - `excess[b]` is the count of `1` minus the count of `0`;
+/// - `min[b]` and `max[b]` are the min and max excess of the non-empty prefixes of `b`.
+pub struct ExcessLookup8 {
+    pub excess: [i8; 256],
+    pub min: [i8; 256],
+    pub max: [i8; 256],
+}
+
+impl ExcessLookup8 {
+    pub fn new() -> Self {
+        let mut s = ExcessLookup8 {
+            excess: [0; 256],
+            min: [0; 256],
+            max: [0; 256],
+        };
+
+        for b in 0..256usize {
+            let mut e = 0;
+            let mut min = i8::MAX;
+            let mut max = i8::MIN;
+
+            for j in 0..8 {
+                e += if b & (1 << j) != 0 { 1 } else { -1 };
+                min = min.min(e);
+                max = max.max(e);
+            }
+
+            s.excess[b] = e;
+            s.min[b] = min;
+            s.max[b] = max;
+        }
+
+        s
+    }
+}
@@ -0,0 +1,25 @@
+use crate::bitmap::excess::ExcessLookup8;
+
+#[test]
+fn test_excess_lookup_8() -> anyhow::Result<()> {
+    let lookup = ExcessLookup8::new();
+
+    let cases = vec![
+        // byte, excess, min, max
+        (0b0000_0000, -8, -8, -1),
+        (0b1111_1111, 8, 1, 8),
+        (0b0000_0001, -6, -6, 1),
+        (0b0101_0101, 0, 0, 1),
+        (0b1010_1010, 0, -1, 0),
+        (0b0011_0011, 0, 0, 2),
+        (0b1111_0000, 0, -4, 0),
+    ];
+
+    for (b, excess, min, max) in cases {
+        assert_eq!(excess, lookup.excess[b], "excess of {:08b}", b);
+        assert_eq!(min, lookup.min[b], "min of {:08b}", b);
+        assert_eq!(max, lookup.max[b], "max of {:08b}", b);
+    }
+
+    Ok(())
+}
@@ -2,7 +2,8 @@ pub trait BuildIndex {
     fn build(words: &[u64]) -> Self;
 }
 
-pub trait RankIndex: BuildIndex {
+/// A rank index does not have to be buildable, e.g., it can be a view of an index built elsewhere.
+pub trait RankIndex {
     /// Calculate the rank at index `i`:
     /// returns the count of `1` upto `i`, exclusive, and the value at `i`(0 or 1).
     fn count_ones(&self, words: &[u64], i: i32) -> (i32, i32);
@@ -10,10 +11,18 @@ pub trait RankIndex: BuildIndex {
     fn get_rank_index(&self) -> &[i32];
 }
 
-pub trait SelectRankIndex: RankIndex + BuildIndex {
+pub trait SelectRankIndex: RankIndex {
     /// Returns the index of the i-th "1".
     /// It requires a rank64 index for speeding up and a select32 index
     fn select_ith_one(&self, words: &[u64], i: i32) -> i32;
 
+    /// Returns the indexes of the i-th "1" and the (i+1)-th "1".
+    fn select_2(&self, words: &[u64], i: i32) -> (i32, i32) {
+        (
+            self.select_ith_one(words, i),
+            self.select_ith_one(words, i + 1),
+        )
+    }
+
     fn get_select_index(&self) -> &[i32];
 }
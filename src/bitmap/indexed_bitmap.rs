@@ -1,28 +1,218 @@
+use std::fmt::Debug;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
 use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
 use crate::bitmap::index::BuildIndex;
-use crate::bitmap::index::RankIndex;
 use crate::bitmap::index::SelectRankIndex;
+use crate::bitmap::rank::rank64;
 use crate::bitmap::rank::RankIndex64;
+use crate::bitmap::select::select0_r64;
 use crate::bitmap::select::SelectIndex32;
+use crate::codec::read_i32_vec;
+use crate::codec::read_u32;
+use crate::codec::read_u64;
+use crate::codec::read_u64_vec;
+use crate::codec::write_i32_slice;
+use crate::codec::write_u32;
+use crate::codec::write_u64;
+use crate::codec::write_u64_slice;
+use crate::codec::Crc32Reader;
+use crate::codec::Crc32Writer;
+use crate::codec::FormatError;
+use crate::static_kv::CTX;
 
-pub struct IndexedBitmap<SI>
-where SI: SelectRankIndex
+/// A bitmap with rank and select index.
+///
+/// The storage `S` of the words can be any container of `u64`, such as `Vec<u64>`, `&[u64]`,
+/// `Box<[u64]>` or `Arc<[u64]>`. The same is true for the storage of the indexes in `SI`.
+pub struct IndexedBitmap<SI, S = Vec<u64>>
+where
+    SI: SelectRankIndex,
+    S: AsRef<[u64]>,
 {
-    pub words: Vec<u64>,
+    pub words: S,
     pub index: SI,
 }
 
-impl BitmapOps for IndexedBitmap<SelectIndex32<RankIndex64>> {
+impl<SI, S> BitmapOps for IndexedBitmap<SI, S>
+where
+    SI: SelectRankIndex,
+    S: AsRef<[u64]>,
+{
+    fn count_ones(&self, i: i32) -> (i32, i32) {
+        self.index.count_ones(self.words.as_ref(), i)
+    }
+
+    fn select_ith_one(&self, i: i32) -> i32 {
+        self.index.select_ith_one(self.words.as_ref(), i)
+    }
+
+    fn select_2(&self, i: i32) -> (i32, i32) {
+        self.index.select_2(self.words.as_ref(), i)
+    }
+
+    fn select_ith_zero(&self, i: i32) -> i32 {
+        select0_r64(self.words.as_ref(), self.index.get_rank_index(), &CTX, i)
+    }
+
+    fn get_bit(&self, i: i32) -> i32 {
+        (self.words.as_ref()[(i >> 6) as usize] >> (i & 63)) as i32 & 1
+    }
+}
+
+impl<SI, S> BuildBitmap for IndexedBitmap<SI, S>
+where
+    SI: SelectRankIndex + BuildIndex,
+    S: AsRef<[u64]> + From<Vec<u64>>,
+{
     fn new(words: Vec<u64>) -> Self {
-        let si = SelectIndex32::<RankIndex64>::build(&words);
-        Self { words, index: si }
+        let si = SI::build(&words);
+        Self {
+            words: words.into(),
+            index: si,
+        }
     }
+}
 
-    fn count_ones(&self, i: i32) -> (i32, i32) {
-        self.index.count_ones(&self.words, i)
+/// Magic number at the start of a serialized `IndexedBitmap`.
+pub const BITMAP_MAGIC: [u8; 4] = *b"SBMP";
+
+/// Current version of the serialized `IndexedBitmap` layout.
+pub const BITMAP_VERSION: u32 = 1;
+
+/// Serialization of an `IndexedBitmap` along with its rank and select index.
+///
+/// All integers are little-endian:
+///
+/// | field          | type              |
+/// | :--            | :--               |
+/// | magic          | `[u8; 4]`: "SBMP" |
+/// | version        | `u32`             |
+/// | words count    | `u64`             |
+/// | rank count     | `u64`             |
+/// | select count   | `u64`             |
+/// | words          | `[u64]`           |
+/// | rank index     | `[i32]`           |
+/// | select index   | `[i32]`           |
+/// | checksum       | `u32`             |
+///
+/// The checksum is a CRC-32 of all the preceding bytes.
+impl<SI, S> IndexedBitmap<SI, S>
+where
+    SI: SelectRankIndex,
+    S: AsRef<[u64]>,
+{
+    #[allow(dead_code)]
+    pub fn write_to<W: Write>(&self, w: W) -> io::Result<()> {
+        let words = self.words.as_ref();
+        let rank_index = self.index.get_rank_index();
+        let select_index = self.index.get_select_index();
+
+        let mut w = Crc32Writer::new(w);
+
+        w.write_all(&BITMAP_MAGIC)?;
+        write_u32(&mut w, BITMAP_VERSION)?;
+        write_u64(&mut w, words.len() as u64)?;
+        write_u64(&mut w, rank_index.len() as u64)?;
+        write_u64(&mut w, select_index.len() as u64)?;
+
+        write_u64_slice(&mut w, words)?;
+        write_i32_slice(&mut w, rank_index)?;
+        write_i32_slice(&mut w, select_index)?;
+
+        let checksum = w.crc.finish();
+        write_u32(&mut w.inner, checksum)?;
+
+        w.flush()
     }
+}
 
-    fn select_ith_one(&self, i: i32) -> i32 {
-        self.index.select_ith_one(&self.words, i)
+impl<I, S> IndexedBitmap<SelectIndex32<RankIndex64<I>, I>, S>
+where
+    I: AsRef<[i32]> + From<Vec<i32>> + Debug + Clone,
+    S: AsRef<[u64]> + From<Vec<u64>>,
+{
+    /// Load a bitmap written by `write_to()`.
+    ///
+    /// The indexes are loaded as is and are validated but never rebuilt.
+    #[allow(dead_code)]
+    pub fn read_from<R: Read>(r: R) -> Result<Self, FormatError> {
+        let mut r = Crc32Reader::new(r);
+
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != BITMAP_MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+
+        let version = read_u32(&mut r)?;
+        if version != BITMAP_VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        let n_words = read_u64(&mut r)? as usize;
+        let n_rank = read_u64(&mut r)? as usize;
+        let n_select = read_u64(&mut r)? as usize;
+
+        if n_words.checked_add(1) != Some(n_rank) {
+            return Err(FormatError::Invalid("rank index size"));
+        }
+
+        let words = read_u64_vec(&mut r, n_words)?;
+        let rank_index = read_i32_vec(&mut r, n_rank)?;
+        let select_index = read_i32_vec(&mut r, n_select)?;
+
+        let checksum = r.crc.finish();
+        if read_u32(&mut r.inner)? != checksum {
+            return Err(FormatError::ChecksumMismatch("bitmap"));
+        }
+
+        validate_index(&words, &rank_index, &select_index)?;
+
+        Ok(Self {
+            words: words.into(),
+            index: SelectIndex32 {
+                index: select_index.into(),
+                rank_index: RankIndex64 {
+                    index: rank_index.into(),
+                },
+                ctx: &CTX,
+            },
+        })
+    }
+}
+
+/// Check the consistency of a bitmap with its rank64 index and select32 index, without rebuilding
+/// them.
+pub fn validate_index(
+    words: &[u64],
+    rank_index: &[i32],
+    select_index: &[i32],
+) -> Result<(), FormatError> {
+    if rank_index.len() != words.len() + 1 || rank_index[0] != 0 {
+        return Err(FormatError::Invalid("rank index size"));
+    }
+
+    for (i, w) in words.iter().enumerate() {
+        if rank_index[i + 1] - rank_index[i] != w.count_ones() as i32 {
+            return Err(FormatError::Invalid("rank index count"));
+        }
     }
+
+    let ones = rank_index[words.len()];
+    if select_index.len() as i32 != (ones + 31) >> 5 {
+        return Err(FormatError::Invalid("select index size"));
+    }
+
+    let bits_count = (words.len() << 6) as i32;
+    for (i, pos) in select_index.iter().copied().enumerate() {
+        if pos < 0 || pos >= bits_count || rank64(words, rank_index, pos) != ((i as i32) << 5, 1) {
+            return Err(FormatError::Invalid("select index position"));
+        }
+    }
+
+    Ok(())
 }
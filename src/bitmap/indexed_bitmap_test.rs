@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::bitmap::index::RankIndex;
+use crate::bitmap::index::SelectRankIndex;
+use crate::bitmap::indexed_bitmap::IndexedBitmap;
+use crate::bitmap::rank::RankIndex64;
+use crate::bitmap::select::SelectIndex32;
+use crate::codec::FormatError;
+use crate::static_kv::LabelBitmap;
+
+type BoxedBitmap = IndexedBitmap<SelectIndex32<RankIndex64<Box<[i32]>>, Box<[i32]>>, Box<[u64]>>;
+type SharedBitmap = IndexedBitmap<SelectIndex32<RankIndex64<Arc<[i32]>>, Arc<[i32]>>, Arc<[u64]>>;
+
+fn bitmap_cases() -> Vec<Vec<u64>> {
+    vec![
+        vec![],
+        vec![0],
+        vec![1],
+        vec![0xffffffffffffffff, 1, 0],
+        vec![0xf0f0f0f0f0f0f0f0, 0x8000000000000001, 3, 0, 0xff],
+    ]
+}
+
+#[test]
+fn test_indexed_bitmap_write_read() -> anyhow::Result<()> {
+    for words in bitmap_cases() {
+        let bm = LabelBitmap::new(words.clone());
+
+        let mut buf = vec![];
+        bm.write_to(&mut buf)?;
+
+        // header + words + rank index + select index + checksum
+        let want_size = 32
+            + words.len() * 8
+            + bm.index.get_rank_index().len() * 4
+            + bm.index.get_select_index().len() * 4
+            + 4;
+        assert_eq!(want_size, buf.len());
+        assert_eq!(b"SBMP", &buf[..4]);
+
+        let got = LabelBitmap::read_from(&buf[..])?;
+
+        assert_eq!(words, got.words);
+        assert_eq!(bm.index.get_rank_index(), got.index.get_rank_index());
+        assert_eq!(bm.index.get_select_index(), got.index.get_select_index());
+
+        for i in 0..(words.len() * 64) as i32 {
+            assert_eq!(bm.count_ones(i), got.count_ones(i));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_indexed_bitmap_read_corrupted() -> anyhow::Result<()> {
+    let bm = LabelBitmap::new(vec![0xf0f0f0f0f0f0f0f0, 3]);
+
+    let mut buf = vec![];
+    bm.write_to(&mut buf)?;
+
+    // truncated at any position
+    for l in 0..buf.len() {
+        let res = LabelBitmap::read_from(&buf[..l]);
+        assert!(matches!(res, Err(FormatError::Truncated)), "len: {}", l);
+    }
+
+    // bad magic
+    let mut b = buf.clone();
+    b[0] = b'X';
+    assert!(matches!(
+        LabelBitmap::read_from(&b[..]),
+        Err(FormatError::BadMagic)
+    ));
+
+    // unsupported version
+    let mut b = buf.clone();
+    b[4] = 2;
+    assert!(matches!(
+        LabelBitmap::read_from(&b[..]),
+        Err(FormatError::UnsupportedVersion(2))
+    ));
+
+    // flipped bit in words
+    let mut b = buf.clone();
+    b[33] ^= 1;
+    assert!(matches!(
+        LabelBitmap::read_from(&b[..]),
+        Err(FormatError::ChecksumMismatch(_))
+    ));
+
+    // inconsistent sizes in header
+    let mut b = buf;
+    b[16] = 5;
+    assert!(matches!(
+        LabelBitmap::read_from(&b[..]),
+        Err(FormatError::Invalid(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_index() {
+    use crate::bitmap::indexed_bitmap::validate_index;
+
+    let bm = LabelBitmap::new(vec![0xf0f0f0f0f0f0f0f0, 3, 0, 0xff]);
+    let words = bm.words.clone();
+    let rank_index = bm.index.get_rank_index().to_vec();
+    let select_index = bm.index.get_select_index().to_vec();
+
+    assert!(validate_index(&words, &rank_index, &select_index).is_ok());
+
+    // words that do not match the rank index
+    let mut w = words.clone();
+    w[1] = 7;
+    assert!(matches!(
+        validate_index(&w, &rank_index, &select_index),
+        Err(FormatError::Invalid("rank index count"))
+    ));
+
+    // rank index of another size
+    assert!(matches!(
+        validate_index(&words[..3], &rank_index, &select_index),
+        Err(FormatError::Invalid("rank index size"))
+    ));
+
+    // select entry that does not point at the (32*i)-th 1
+    let mut s = select_index;
+    s[1] += 1;
+    assert!(matches!(
+        validate_index(&words, &rank_index, &s),
+        Err(FormatError::Invalid("select index position"))
+    ));
+}
+
+#[test]
+fn test_indexed_bitmap_storage() -> anyhow::Result<()> {
+    for words in bitmap_cases() {
+        let owned = LabelBitmap::new(words.clone());
+
+        let boxed: BoxedBitmap = IndexedBitmap::new(words.clone());
+
+        let shared: SharedBitmap = IndexedBitmap::new(words.clone());
+
+        let borrowed = IndexedBitmap {
+            words: &owned.words[..],
+            index: SelectIndex32 {
+                index: owned.index.get_select_index(),
+                rank_index: RankIndex64 {
+                    index: owned.index.get_rank_index(),
+                },
+                ctx: owned.index.ctx,
+            },
+        };
+
+        for i in 0..(words.len() * 64) as i32 {
+            let want = owned.count_ones(i);
+            assert_eq!(want, boxed.count_ones(i));
+            assert_eq!(want, shared.count_ones(i));
+            assert_eq!(want, borrowed.count_ones(i));
+            assert_eq!(want.1, borrowed.get_bit(i));
+        }
+
+        let ones = owned.index.get_rank_index()[words.len()];
+        for i in 0..ones {
+            let want = owned.select_ith_one(i);
+            assert_eq!(want, boxed.select_ith_one(i));
+            assert_eq!(want, shared.select_ith_one(i));
+            assert_eq!(want, borrowed.select_ith_one(i));
+        }
+
+        // serialize a borrowed bitmap and load it into a shared one.
+        let mut buf = vec![];
+        borrowed.write_to(&mut buf)?;
+
+        let loaded: SharedBitmap = IndexedBitmap::read_from(&buf[..])?;
+        assert_eq!(&words[..], &loaded.words[..]);
+    }
+
+    Ok(())
+}
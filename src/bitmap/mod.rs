@@ -1,16 +1,24 @@
 pub mod bitmap_ops;
 pub mod context;
+pub mod excess;
+#[cfg(test)]
+mod excess_test;
 pub mod fmt;
 #[cfg(test)]
 mod fmt_test;
 pub mod index;
 pub mod indexed_bitmap;
+#[cfg(test)]
+mod indexed_bitmap_test;
 pub mod mask;
 #[cfg(test)]
 mod mask_test;
 pub mod rank;
 #[cfg(test)]
 mod rank_test;
+pub mod rle_bitmap;
+#[cfg(test)]
+mod rle_bitmap_test;
 pub mod select;
 #[cfg(test)]
 mod select_test;
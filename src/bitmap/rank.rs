@@ -1,25 +1,35 @@
 use crate::bitmap::index::BuildIndex;
 use crate::bitmap::index::RankIndex;
 
+/// The storage `I` of the index can be any container of `i32`, such as `Vec<i32>`, `&[i32]`,
+/// `Box<[i32]>` or `Arc<[i32]>`.
 #[derive(Debug, Clone)]
-pub struct RankIndex64 {
-    pub index: Vec<i32>,
+pub struct RankIndex64<I = Vec<i32>>
+where I: AsRef<[i32]>
+{
+    pub index: I,
 }
 
-impl BuildIndex for RankIndex64 {
+impl<I> BuildIndex for RankIndex64<I>
+where I: AsRef<[i32]> + From<Vec<i32>>
+{
     fn build(words: &[u64]) -> Self {
         let index = build_rank64_index(words);
-        RankIndex64 { index }
+        RankIndex64 {
+            index: index.into(),
+        }
     }
 }
 
-impl RankIndex for RankIndex64 {
+impl<I> RankIndex for RankIndex64<I>
+where I: AsRef<[i32]>
+{
     fn count_ones(&self, words: &[u64], i: i32) -> (i32, i32) {
-        rank64(words, &self.index, i)
+        rank64(words, self.index.as_ref(), i)
     }
 
     fn get_rank_index(&self) -> &[i32] {
-        &self.index
+        self.index.as_ref()
     }
 }
 
@@ -44,7 +54,7 @@ fn build_rank64_index(words: &[u64]) -> Vec<i32> {
 
 /// Find the i-th `1` with the help of a pre-calc index.
 #[allow(dead_code)]
-fn rank64(words: &[u64], rank_index: &[i32], i: i32) -> (i32, i32) {
+pub fn rank64(words: &[u64], rank_index: &[i32], i: i32) -> (i32, i32) {
     let word_idx = (i >> 6) as usize;
     let in_word_idx = i & 63;
 
@@ -48,7 +48,7 @@ fn test_rank64() -> anyhow::Result<()> {
     for (_i, c) in cases.iter().enumerate() {
         // test building index
 
-        let index = RankIndex64::build(&c.bm);
+        let index = RankIndex64::<Vec<i32>>::build(&c.bm);
         assert_eq!(c.want64, index.index);
 
         // test rank 64
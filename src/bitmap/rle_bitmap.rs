@@ -0,0 +1,161 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+
+/// A run-length encoded bitmap.
+///
+/// It stores only the runs of `1`, thus its size is proportional to the number of runs, not to the
+/// length of the bitmap.
+/// rank and select are done by a binary search over the run boundaries.
+///
+/// E.g.:
+///     bitmap  = 0011100001..
+///     starts  = [2, 9, ..]
+///     ones    = [0, 3, ..]
+#[derive(Debug, Clone)]
+pub struct RleBitmap {
+    /// The starting position of every run of `1`.
+    pub starts: Vec<i32>,
+
+    /// The count of `1` before every run of `1`.
+    /// It has one more element than `starts`: the last one is the total count of `1`.
+    pub ones: Vec<i32>,
+
+    /// The number of bits in the bitmap.
+    pub len: i32,
+}
+
+impl RleBitmap {
+    /// Build from a sorted list of non-overlapping runs of `1`, in form of `(start, length)`.
+    #[allow(dead_code)]
+    pub fn from_runs(runs: &[(i32, i32)], len: i32) -> Self {
+        let mut starts = Vec::with_capacity(runs.len());
+        let mut ones = Vec::with_capacity(runs.len() + 1);
+
+        let mut n = 0;
+
+        for (start, l) in runs.iter().copied() {
+            if l == 0 {
+                continue;
+            }
+
+            let last = starts.len();
+
+            if last > 0 && starts[last - 1] + (n - ones[last - 1]) == start {
+                // adjacent to the previous run, merge them.
+                n += l;
+                continue;
+            }
+
+            starts.push(start);
+            ones.push(n);
+            n += l;
+        }
+
+        ones.push(n);
+
+        RleBitmap { starts, ones, len }
+    }
+
+    /// Returns the number of runs of `1`.
+    #[allow(dead_code)]
+    pub fn runs(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns the total count of `1`.
+    #[allow(dead_code)]
+    pub fn count(&self) -> i32 {
+        self.ones[self.starts.len()]
+    }
+
+    /// Returns the end position(exclusive) of the k-th run of `1`.
+    fn run_end(&self, k: usize) -> i32 {
+        self.starts[k] + self.ones[k + 1] - self.ones[k]
+    }
+}
+
+impl BuildBitmap for RleBitmap {
+    fn new(words: Vec<u64>) -> Self {
+        let mut runs = vec![];
+
+        let bits_count = (words.len() << 6) as i32;
+
+        let mut i = 0;
+        while i < bits_count {
+            // skip to the next `1`
+            let w = words[(i >> 6) as usize] >> (i & 63);
+            if w == 0 {
+                i = (i | 63) + 1;
+                continue;
+            }
+            i += w.trailing_zeros() as i32;
+
+            let start = i;
+
+            // skip to the next `0`
+            loop {
+                let w = !words[(i >> 6) as usize] >> (i & 63);
+                if w == 0 {
+                    i = (i | 63) + 1;
+                    if i < bits_count {
+                        continue;
+                    }
+                } else {
+                    i += w.trailing_zeros() as i32;
+                }
+                break;
+            }
+
+            runs.push((start, i - start));
+        }
+
+        Self::from_runs(&runs, bits_count)
+    }
+}
+
+impl BitmapOps for RleBitmap {
+    fn count_ones(&self, i: i32) -> (i32, i32) {
+        debug_assert!(i < self.len);
+
+        // the number of runs that start at or before i.
+        let k = self.starts.partition_point(|x| *x <= i);
+
+        if k == 0 {
+            return (0, 0);
+        }
+
+        let k = k - 1;
+        let end = self.run_end(k);
+
+        if i < end {
+            (self.ones[k] + i - self.starts[k], 1)
+        } else {
+            (self.ones[k + 1], 0)
+        }
+    }
+
+    fn select_ith_one(&self, i: i32) -> i32 {
+        // the run that contains the i-th `1`.
+        let k = self.ones.partition_point(|x| *x <= i) - 1;
+
+        self.starts[k] + i - self.ones[k]
+    }
+
+    fn select_ith_zero(&self, i: i32) -> i32 {
+        // The count of "0" before the k-th run of "1" is `starts[k] - ones[k]`.
+        // Find the number of runs of "1" before the i-th "0".
+        let mut lo = 0;
+        let mut hi = self.starts.len();
+
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.starts[mid] - self.ones[mid] <= i {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        i + self.ones[lo]
+    }
+}
@@ -0,0 +1,97 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::static_kv::LabelBitmap;
+
+#[test]
+fn test_rle_bitmap_build() -> anyhow::Result<()> {
+    let cases: Vec<(Vec<u64>, Vec<i32>, Vec<i32>)> = vec![
+        (vec![], vec![], vec![0]),
+        (vec![0], vec![], vec![0]),
+        (vec![1], vec![0], vec![0, 1]),
+        (vec![0b0110_0111], vec![0, 5], vec![0, 3, 5]),
+        (vec![0xffffffffffffffff], vec![0], vec![0, 64]),
+        (vec![0xffffffffffffffff, 1], vec![0], vec![0, 65]),
+        (vec![0x8000000000000000, 0xff00], vec![63, 72], vec![
+            0, 1, 9,
+        ]),
+        (
+            vec![0, 0, 0xffffffffffffffff, 0xffffffffffffffff],
+            vec![128],
+            vec![0, 128],
+        ),
+    ];
+
+    for (words, want_starts, want_ones) in cases {
+        let bm = RleBitmap::new(words.clone());
+        assert_eq!(want_starts, bm.starts, "starts of {:?}", words);
+        assert_eq!(want_ones, bm.ones, "ones of {:?}", words);
+        assert_eq!((words.len() * 64) as i32, bm.len);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_rle_bitmap_from_runs() -> anyhow::Result<()> {
+    let bm = RleBitmap::from_runs(&[(1, 2), (3, 1), (5, 0), (10, 4)], 20);
+
+    assert_eq!(vec![1, 10], bm.starts);
+    assert_eq!(vec![0, 3, 7], bm.ones);
+    assert_eq!(2, bm.runs());
+    assert_eq!(7, bm.count());
+
+    Ok(())
+}
+
+#[test]
+fn test_rle_bitmap_rank_select() -> anyhow::Result<()> {
+    let cases = vec![
+        vec![0],
+        vec![1],
+        vec![0xf0f0f0f0f0f0f0f0],
+        vec![0xffffffffffffffff, 0],
+        vec![0, 0xffffffffffffffff, 0xffffffffffffffff, 1],
+        vec![0x8000000000000001, 0x8000000000000001, 0x8000000000000001],
+        vec![0b000101100110011, 0, 0x00ff00ff00ff00ff],
+    ];
+
+    for words in cases {
+        let rle = RleBitmap::new(words.clone());
+        let indexed = LabelBitmap::new(words.clone());
+
+        for i in 0..(words.len() * 64) as i32 {
+            assert_eq!(
+                indexed.count_ones(i),
+                rle.count_ones(i),
+                "count_ones({}) of {:?}",
+                i,
+                words
+            );
+            assert_eq!(indexed.count_zeros(i), rle.count_zeros(i));
+        }
+
+        let zeros = rle.len - rle.count();
+        for i in 0..zeros {
+            assert_eq!(
+                indexed.select_ith_zero(i),
+                rle.select_ith_zero(i),
+                "select_ith_zero({}) of {:?}",
+                i,
+                words
+            );
+        }
+
+        for i in 0..rle.count() {
+            assert_eq!(
+                indexed.select_ith_one(i),
+                rle.select_ith_one(i),
+                "select_ith_one({}) of {:?}",
+                i,
+                words
+            );
+        }
+    }
+
+    Ok(())
+}
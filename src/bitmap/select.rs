@@ -48,23 +48,28 @@ impl SelectLookup8 {
 ///     select(bitmap, 1) = 3
 ///
 /// It stores the value of select(i*32) for every i.
+/// The storage `I` of the index can be any container of `i32`, as `RankIndex64` does.
 #[derive(Clone)]
-pub struct SelectIndex32<RI>
-where RI: RankIndex + Debug + Clone
+pub struct SelectIndex32<RI, I = Vec<i32>>
+where
+    RI: RankIndex + Debug + Clone,
+    I: AsRef<[i32]>,
 {
-    pub index: Vec<i32>,
+    pub index: I,
     pub rank_index: RI,
 
     pub ctx: &'static Context,
 }
 
-impl<RI> BuildIndex for SelectIndex32<RI>
-where RI: RankIndex + Debug + Clone
+impl<RI, I> BuildIndex for SelectIndex32<RI, I>
+where
+    RI: RankIndex + BuildIndex + Debug + Clone,
+    I: AsRef<[i32]> + From<Vec<i32>>,
 {
     fn build(words: &[u64]) -> Self {
         let index = build_select32_index(words);
         SelectIndex32 {
-            index,
+            index: index.into(),
             rank_index: RI::build(words),
             ctx: &*CTX,
         }
@@ -72,29 +77,47 @@ where RI: RankIndex + Debug + Clone
 }
 
 /// SelectIndex32 depends on a rank index.
-impl RankIndex for SelectIndex32<RankIndex64> {
+impl<RI, I> RankIndex for SelectIndex32<RankIndex64<RI>, I>
+where
+    RI: AsRef<[i32]> + Debug + Clone,
+    I: AsRef<[i32]>,
+{
     fn count_ones(&self, words: &[u64], i: i32) -> (i32, i32) {
         self.rank_index.count_ones(words, i)
     }
 
     fn get_rank_index(&self) -> &[i32] {
-        &self.rank_index.get_rank_index()
+        self.rank_index.get_rank_index()
     }
 }
 
-impl SelectRankIndex for SelectIndex32<RankIndex64> {
+impl<RI, I> SelectRankIndex for SelectIndex32<RankIndex64<RI>, I>
+where
+    RI: AsRef<[i32]> + Debug + Clone,
+    I: AsRef<[i32]>,
+{
     fn select_ith_one(&self, words: &[u64], i: i32) -> i32 {
         select_s32_r64(
             words,
-            &self.get_select_index(),
-            &self.rank_index.get_rank_index(),
+            self.get_select_index(),
+            self.rank_index.get_rank_index(),
+            self.ctx,
+            i,
+        )
+    }
+
+    fn select_2(&self, words: &[u64], i: i32) -> (i32, i32) {
+        select_2_s32_r64(
+            words,
+            self.get_select_index(),
+            self.rank_index.get_rank_index(),
             self.ctx,
             i,
         )
     }
 
     fn get_select_index(&self) -> &[i32] {
-        &self.index
+        self.index.as_ref()
     }
 }
 
@@ -147,47 +170,9 @@ pub fn select_s32_r64(
         word_i += 1;
     }
 
-    let w = words[word_i];
-    let mut ww = w;
-
     let base = (word_i << 6) as i32;
 
-    // To find the `find_ith` `1` in words[word_i],
-    // with a expanded binary search.
-
-    let mut find_ith: u32 = (i - rank_index[word_i]) as u32;
-
-    let mut offset = 0;
-
-    // count of `1` in the least significant 32 bits.
-    let ones = (ww as u32).count_ones();
-    if ones <= find_ith {
-        find_ith -= ones;
-        offset += 32;
-        ww >>= 32;
-    }
-
-    // count of `1` in the [32, 32+16] bits.
-    let ones = (ww as u16).count_ones();
-    if ones <= find_ith {
-        find_ith -= ones;
-        offset |= 16;
-        ww >>= 16;
-    }
-
-    let ones = (ww as u8).count_ones();
-
-    if ones <= find_ith {
-        // The `1` to find is in the second 8 bits.
-
-        let x = (((ww as usize) >> 5) & 0x7f8) | ((find_ith - ones) as usize);
-        base + context.select_lookup_8.lookup[x] as i32 + offset + 8
-    } else {
-        // The `1` to find is in the first 8 bits.
-
-        let x = ((ww as usize) & 0xff) << 3 | (find_ith as usize);
-        base + context.select_lookup_8.lookup[x] as i32 + offset
-    }
+    base + select_u64(context, words[word_i], (i - rank_index[word_i]) as u32)
 }
 
 /// Select32R64 returns the indexes of the i-th "1" and the (i+1)-th "1".
@@ -279,3 +264,70 @@ pub fn select_2_s32_r64(
 
     (in_word_idx, (l << 6) as i32)
 }
+
+/// Returns the index of the i-th "1" in a word.
+/// `i` must be less than the count of "1" in the word.
+pub fn select_u64(context: &Context, w: u64, i: u32) -> i32 {
+    let mut ww = w;
+    let mut find_ith = i;
+    let mut offset = 0;
+
+    // count of `1` in the least significant 32 bits.
+    let ones = (ww as u32).count_ones();
+    if ones <= find_ith {
+        find_ith -= ones;
+        offset += 32;
+        ww >>= 32;
+    }
+
+    // count of `1` in the [32, 32+16] bits.
+    let ones = (ww as u16).count_ones();
+    if ones <= find_ith {
+        find_ith -= ones;
+        offset |= 16;
+        ww >>= 16;
+    }
+
+    let ones = (ww as u8).count_ones();
+
+    if ones <= find_ith {
+        // The `1` to find is in the second 8 bits.
+
+        let x = (((ww as usize) >> 5) & 0x7f8) | ((find_ith - ones) as usize);
+        context.select_lookup_8.lookup[x] as i32 + offset + 8
+    } else {
+        // The `1` to find is in the first 8 bits.
+
+        let x = ((ww as usize) & 0xff) << 3 | (find_ith as usize);
+        context.select_lookup_8.lookup[x] as i32 + offset
+    }
+}
+
+/// Returns the index of the i-th "0".
+/// It does a binary search on a rank64 index to find the word containing it.
+/// If there are no more than i "0", it returns the bit length of `words`,
+/// e.g., 0 for an empty bitmap.
+pub fn select0_r64(words: &[u64], rank_index: &[i32], context: &Context, i: i32) -> i32 {
+    let zeros_before = |word_i: usize| ((word_i << 6) as i32) - rank_index[word_i];
+
+    if i < 0 || zeros_before(words.len()) <= i {
+        return (words.len() << 6) as i32;
+    }
+
+    // find the last word that has at most i "0" before it.
+    let mut lo = 0;
+    let mut hi = words.len();
+
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if zeros_before(mid) <= i {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let find_ith = (i - zeros_before(lo)) as u32;
+
+    ((lo << 6) as i32) + select_u64(context, !words[lo], find_ith)
+}
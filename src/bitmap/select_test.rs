@@ -1,5 +1,9 @@
+use crate::bitmap::context::Context;
 use crate::bitmap::index::BuildIndex;
 use crate::bitmap::index::SelectRankIndex;
+use crate::bitmap::rank::RankIndex64;
+use crate::bitmap::select::select0_r64;
+use crate::bitmap::select::select_u64;
 use crate::bitmap::select::SelectIndex32;
 use crate::bitmap::select::SelectLookup8;
 
@@ -45,18 +49,18 @@ fn test_select_32_r64() -> anyhow::Result<()> {
     ];
 
     for (_i, c) in cases.iter().enumerate() {
-        let sidx = SelectIndex32::build(c);
+        let sidx = SelectIndex32::<RankIndex64>::build(c);
 
         let all = to_array(c);
 
         if all.len() > 0 {
             for j in 0..(all.len() - 1) {
-                // let (a, b) = select_2_s32_r64(c, &sidx.index, &sidx.rank_index.index, &ctx, j as i32);
-                // assert_eq!(all[j], a, "select: first: ");
-                // assert_eq!(all[j + 1], b, "select: second: ");
-
                 let a = sidx.select_ith_one(c, j as i32);
                 assert_eq!(all[j], a, "select: first: ");
+
+                let (a, b) = sidx.select_2(c, j as i32);
+                assert_eq!(all[j], a, "select: first: ");
+                assert_eq!(all[j + 1], b, "select: second: ");
             }
         }
     }
@@ -76,3 +80,71 @@ fn to_array(words: &[u64]) -> Vec<i32> {
 
     return r;
 }
+
+#[test]
+fn test_select0_r64() -> anyhow::Result<()> {
+    let ctx = Context::new();
+
+    let cases = vec![
+        vec![0],
+        vec![1],
+        vec![0xfffffffffffffffe],
+        vec![0xffffffffffffffff, 0],
+        vec![0xffffffffffffffff, 0xffffffffffffffff, 0x7fffffffffffffff],
+        vec![0xf0f0f0f0f0f0f0f0, 0, 0xffffffff00000000],
+        vec![0b000101100110011, 0xaaaaaaaaaaaaaaaa],
+    ];
+
+    for c in cases {
+        let rank_index = RankIndex64::<Vec<i32>>::build(&c);
+        let zeros = to_array(&c.iter().map(|x| !x).collect::<Vec<_>>());
+
+        for (j, want) in zeros.iter().enumerate() {
+            let got = select0_r64(&c, &rank_index.index, &ctx, j as i32);
+            assert_eq!(*want, got, "select0 {}-th of {:?}", j, c);
+        }
+
+        // out of range returns the bit length.
+        let got = select0_r64(&c, &rank_index.index, &ctx, zeros.len() as i32);
+        assert_eq!(
+            (c.len() * 64) as i32,
+            got,
+            "select0 out of range of {:?}",
+            c
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_select0_r64_empty() -> anyhow::Result<()> {
+    let ctx = Context::new();
+
+    let words: Vec<u64> = vec![];
+    let rank_index = RankIndex64::<Vec<i32>>::build(&words);
+
+    assert_eq!(0, select0_r64(&words, &rank_index.index, &ctx, 0));
+    assert_eq!(0, select0_r64(&words, &rank_index.index, &ctx, 5));
+
+    Ok(())
+}
+
+#[test]
+fn test_select_u64() -> anyhow::Result<()> {
+    let ctx = Context::new();
+
+    for w in [
+        1u64,
+        0x8000000000000000,
+        0xffffffffffffffff,
+        0x0123456789abcdef,
+    ] {
+        let ones = to_array(&[w]);
+        for (j, want) in ones.iter().enumerate() {
+            assert_eq!(*want, select_u64(&ctx, w, j as u32), "{}-th of {:x}", j, w);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use std::fmt;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+/// Error returned when decoding a serialized data structure.
+#[derive(Debug)]
+pub enum FormatError {
+    /// An underlying IO error.
+    Io(io::Error),
+
+    /// The data ends before all expected bytes are read.
+    Truncated,
+
+    /// The leading magic number does not match.
+    BadMagic,
+
+    /// The format version is not supported.
+    UnsupportedVersion(u32),
+
+    /// The checksum of the named part does not match.
+    ChecksumMismatch(&'static str),
+
+    /// The data is well formed but its content is inconsistent.
+    Invalid(&'static str),
+
+    /// The data can not be borrowed in place: it is not aligned or the platform is not
+    /// little-endian.
+    Misaligned,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Io(e) => write!(f, "io error: {}", e),
+            FormatError::Truncated => write!(f, "data is truncated"),
+            FormatError::BadMagic => write!(f, "bad magic number"),
+            FormatError::UnsupportedVersion(v) => write!(f, "unsupported version: {}", v),
+            FormatError::ChecksumMismatch(part) => write!(f, "checksum mismatch: {}", part),
+            FormatError::Invalid(reason) => write!(f, "invalid data: {}", reason),
+            FormatError::Misaligned => write!(f, "data can not be borrowed in place"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FormatError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            FormatError::Truncated
+        } else {
+            FormatError::Io(e)
+        }
+    }
+}
+
+/// Lookup table for CRC-32(IEEE 802.3, reflected polynomial 0xedb88320).
+const CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+
+    table
+}
+
+/// Incremental CRC-32 checksum.
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Crc32 { state: 0xffffffff }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        let mut c = self.state;
+        for b in data {
+            c = CRC32_TABLE[((c ^ *b as u32) & 0xff) as usize] ^ (c >> 8);
+        }
+        self.state = c;
+    }
+
+    pub fn finish(&self) -> u32 {
+        self.state ^ 0xffffffff
+    }
+}
+
+/// Calculate CRC-32 of a byte slice.
+#[allow(dead_code)]
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut c = Crc32::new();
+    c.update(data);
+    c.finish()
+}
+
+/// A writer that updates a checksum with every byte written through it.
+pub struct Crc32Writer<W: Write> {
+    pub inner: W,
+    pub crc: Crc32,
+}
+
+impl<W: Write> Crc32Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Crc32Writer {
+            inner,
+            crc: Crc32::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for Crc32Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader that updates a checksum with every byte read through it.
+pub struct Crc32Reader<R: Read> {
+    pub inner: R,
+    pub crc: Crc32,
+}
+
+impl<R: Read> Crc32Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Crc32Reader {
+            inner,
+            crc: Crc32::new(),
+        }
+    }
+}
+
+impl<R: Read> Read for Crc32Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Max number of elements to pre-allocate before reading them.
+/// A length field in corrupted data must not lead to a huge allocation.
+const MAX_PREALLOC: usize = 1 << 16;
+
+pub fn write_u32<W: Write>(w: &mut W, v: u32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+pub fn write_u64<W: Write>(w: &mut W, v: u64) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+pub fn write_u64_slice<W: Write>(w: &mut W, vs: &[u64]) -> io::Result<()> {
+    for v in vs {
+        w.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn write_i32_slice<W: Write>(w: &mut W, vs: &[i32]) -> io::Result<()> {
+    for v in vs {
+        w.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn read_u32<R: Read>(r: &mut R) -> Result<u32, FormatError> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub fn read_u64<R: Read>(r: &mut R) -> Result<u64, FormatError> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+pub fn read_u64_vec<R: Read>(r: &mut R, n: usize) -> Result<Vec<u64>, FormatError> {
+    let mut res = Vec::with_capacity(n.min(MAX_PREALLOC));
+    for _ in 0..n {
+        res.push(read_u64(r)?);
+    }
+    Ok(res)
+}
+
+pub fn read_i32_vec<R: Read>(r: &mut R, n: usize) -> Result<Vec<i32>, FormatError> {
+    let mut res = Vec::with_capacity(n.min(MAX_PREALLOC));
+    for _ in 0..n {
+        res.push(read_u32(r)? as i32);
+    }
+    Ok(res)
+}
+
+/// Reinterpret little-endian bytes as a slice of `u64` without copying.
+pub fn cast_u64_slice(b: &[u8]) -> Result<&[u64], FormatError> {
+    if cfg!(target_endian = "big") {
+        return Err(FormatError::Misaligned);
+    }
+
+    // Safe: any bit pattern is a valid u64.
+    let (prefix, vs, suffix) = unsafe { b.align_to::<u64>() };
+    if !prefix.is_empty() || !suffix.is_empty() {
+        return Err(FormatError::Misaligned);
+    }
+
+    Ok(vs)
+}
+
+/// Reinterpret little-endian bytes as a slice of `i32` without copying.
+pub fn cast_i32_slice(b: &[u8]) -> Result<&[i32], FormatError> {
+    if cfg!(target_endian = "big") {
+        return Err(FormatError::Misaligned);
+    }
+
+    // Safe: any bit pattern is a valid i32.
+    let (prefix, vs, suffix) = unsafe { b.align_to::<i32>() };
+    if !prefix.is_empty() || !suffix.is_empty() {
+        return Err(FormatError::Misaligned);
+    }
+
+    Ok(vs)
+}
@@ -0,0 +1,68 @@
+use std::io::Read;
+use std::io::Write;
+
+use crate::codec::crc32;
+use crate::codec::read_i32_vec;
+use crate::codec::read_u64_vec;
+use crate::codec::write_i32_slice;
+use crate::codec::write_u64_slice;
+use crate::codec::Crc32;
+use crate::codec::Crc32Reader;
+use crate::codec::Crc32Writer;
+use crate::codec::FormatError;
+
+#[test]
+fn test_crc32() -> anyhow::Result<()> {
+    assert_eq!(0, crc32(b""));
+    assert_eq!(0xe8b7be43, crc32(b"a"));
+    assert_eq!(0xcbf43926, crc32(b"123456789"));
+
+    // incremental update
+    let mut c = Crc32::new();
+    c.update(b"1234");
+    c.update(b"56789");
+    assert_eq!(0xcbf43926, c.finish());
+
+    Ok(())
+}
+
+#[test]
+fn test_crc32_reader_writer() -> anyhow::Result<()> {
+    let mut w = Crc32Writer::new(vec![]);
+    w.write_all(b"123456789")?;
+    assert_eq!(0xcbf43926, w.crc.finish());
+    assert_eq!(b"123456789".to_vec(), w.inner);
+
+    let mut r = Crc32Reader::new(&b"123456789"[..]);
+    let mut buf = vec![];
+    r.read_to_end(&mut buf)?;
+    assert_eq!(0xcbf43926, r.crc.finish());
+
+    Ok(())
+}
+
+#[test]
+fn test_read_write_slice() -> anyhow::Result<()> {
+    let mut buf = vec![];
+    write_u64_slice(&mut buf, &[1, 0x0102030405060708])?;
+    write_i32_slice(&mut buf, &[-1, 2])?;
+
+    assert_eq!(
+        vec![
+            1, 0, 0, 0, 0, 0, 0, 0, //
+            8, 7, 6, 5, 4, 3, 2, 1, //
+            255, 255, 255, 255, //
+            2, 0, 0, 0,
+        ],
+        buf
+    );
+
+    let mut r = &buf[..];
+    assert_eq!(vec![1, 0x0102030405060708], read_u64_vec(&mut r, 2)?);
+    assert_eq!(vec![-1, 2], read_i32_vec(&mut r, 2)?);
+
+    let res = read_i32_vec(&mut r, 1);
+    assert!(matches!(res, Err(FormatError::Truncated)));
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::int_vec::IntVec;
+
+/// A sequence of integers in directly addressable codes(DAC).
+///
+/// Every value is split into chunks of `width` bits, from the least significant one. The l-th
+/// chunks of all values having more than `l` chunks are stored in `chunks[l]`, in the order of the
+/// values.
+///
+/// `more[l]` has a `1` at position `i` if the value of `chunks[l][i]` has more chunks. Then its
+/// next chunk is at `count_ones(i)` in `chunks[l + 1]`.
+///
+/// Small values take a few chunks while large values do not make every value large.
+pub struct Dac<BM>
+where BM: BitmapOps
+{
+    pub chunks: Vec<IntVec>,
+    pub more: Vec<BM>,
+
+    /// The number of bits of a chunk.
+    pub width: u32,
+
+    /// The number of values.
+    pub len: i32,
+}
+
+impl<BM> Dac<BM>
+where BM: BuildBitmap
+{
+    /// Build with chunks of `width` bits.
+    #[allow(dead_code)]
+    pub fn new(values: &[u64], width: u32) -> Self {
+        assert!(
+            (1..=64).contains(&width),
+            "width must be in [1, 64]: {}",
+            width
+        );
+
+        let mut chunks = vec![];
+        let mut more = vec![];
+
+        let mut cur = values.to_vec();
+        let mut next = vec![];
+
+        while !cur.is_empty() {
+            let mut cs = IntVec::new(width);
+
+            // One more word to make `count_ones(len)` valid.
+            let mut words = vec![0u64; (cur.len() >> 6) + 1];
+
+            next.clear();
+
+            for (i, v) in cur.iter().enumerate() {
+                cs.push(*v & cs.ctx.masks.mask[width as usize]);
+
+                let rest = if width == 64 { 0 } else { *v >> width };
+                if rest != 0 {
+                    words[i >> 6] |= 1 << (i & 63);
+                    next.push(rest);
+                }
+            }
+
+            chunks.push(cs);
+            more.push(BM::new(words));
+
+            std::mem::swap(&mut cur, &mut next);
+        }
+
+        Dac {
+            chunks,
+            more,
+            width,
+            len: values.len() as i32,
+        }
+    }
+}
+
+impl<BM> Dac<BM>
+where BM: BitmapOps
+{
+    /// Returns the value at position `i`.
+    #[allow(dead_code)]
+    pub fn get(&self, i: i32) -> u64 {
+        assert!(
+            0 <= i && i < self.len,
+            "index out of range: {} >= {}",
+            i,
+            self.len
+        );
+
+        let mut i = i;
+        let mut v = 0;
+
+        for (l, (cs, bm)) in self.chunks.iter().zip(self.more.iter()).enumerate() {
+            v |= cs.get(i as usize) << (l as u32 * self.width);
+
+            let (ones, bit) = bm.count_ones(i);
+            if bit == 0 {
+                break;
+            }
+
+            // The rank of the `1` is the position at the next level.
+            i = ones;
+        }
+
+        v
+    }
+
+    #[allow(dead_code)]
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+}
@@ -0,0 +1,46 @@
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::dac::Dac;
+use crate::static_kv::LabelBitmap;
+
+#[test]
+fn test_dac_example() -> anyhow::Result<()> {
+    let d = Dac::<LabelBitmap>::new(&[1, 300, 7, 70000], 8);
+
+    assert_eq!(3, d.chunks.len());
+    assert_eq!(4, d.chunks[0].len());
+    assert_eq!(2, d.chunks[1].len());
+    assert_eq!(1, d.chunks[2].len());
+
+    assert_eq!(1, d.get(0));
+    assert_eq!(300, d.get(1));
+    assert_eq!(7, d.get(2));
+    assert_eq!(70000, d.get(3));
+
+    Ok(())
+}
+
+#[test]
+fn test_dac() -> anyhow::Result<()> {
+    // mostly tiny values with a long tail
+    let mut values = (0..3000u64)
+        .map(|i| {
+            let x = i.wrapping_mul(0x9e3779b97f4a7c15);
+            x >> (x % 64)
+        })
+        .collect::<Vec<_>>();
+    values.push(u64::MAX);
+    values.push(0);
+
+    for width in [1, 3, 4, 8, 13, 32, 63, 64] {
+        let d = Dac::<LabelBitmap>::new(&values, width);
+        assert_eq!(values, d.iter().collect::<Vec<_>>(), "width: {}", width);
+
+        let d = Dac::<RleBitmap>::new(&values, width);
+        assert_eq!(values, d.iter().collect::<Vec<_>>(), "width: {}", width);
+    }
+
+    let d = Dac::<LabelBitmap>::new(&[], 8);
+    assert_eq!(0, d.iter().count());
+
+    Ok(())
+}
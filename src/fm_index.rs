@@ -0,0 +1,290 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::int_vec::IntVec;
+use crate::wavelet_tree::WaveletTree;
+
+/// An FM-index over a byte text, for counting and locating substrings.
+///
+/// The text is terminated by a sentinel `$` that is less than any byte. Its Burrows-Wheeler
+/// transform(BWT) is stored in a wavelet tree, with `0` in place of the `$` at row `dollar`.
+///
+/// `sampled` marks the rows whose suffix array value is a multiple of `sample_rate`, these values
+/// are stored in `sa_samples` in row order. `isa_samples[k]` is the row of the suffix starting at
+/// `k * sample_rate`.
+pub struct FmIndex<BM>
+where BM: BitmapOps
+{
+    pub bwt: WaveletTree<BM>,
+
+    /// The row at which the BWT is `$`.
+    pub dollar: i32,
+
+    /// `c[b]` is the number of rows starting with a symbol less than byte `b`, including `$`.
+    pub c: Vec<i32>,
+
+    pub sampled: BM,
+    pub sa_samples: IntVec,
+    pub isa_samples: IntVec,
+    pub sample_rate: i32,
+
+    /// The length of the text, without `$`.
+    pub len: i32,
+
+    /// The start of every key in the text if it is built from keys, with the text length as the
+    /// last element.
+    pub key_starts: Vec<i32>,
+}
+
+impl<BM> FmIndex<BM>
+where BM: BuildBitmap
+{
+    /// Build over `text`, sampling one of every `sample_rate` suffix array values.
+    #[allow(dead_code)]
+    pub fn new(text: &[u8], sample_rate: i32) -> Self {
+        assert!(sample_rate > 0, "sample_rate must be positive");
+
+        let n = text.len();
+        let sa = suffix_array(text);
+
+        let mut bwt = Vec::with_capacity(n + 1);
+        let mut dollar = 0;
+
+        // One more word to make `count_ones(len)` valid.
+        let mut sampled = vec![0u64; ((n + 1) >> 6) + 1];
+        let mut sa_samples = vec![];
+        let mut isa_samples = vec![0u64; (n + sample_rate as usize) / sample_rate as usize];
+
+        for (row, p) in sa.iter().enumerate() {
+            let p = *p as usize;
+
+            if p == 0 {
+                dollar = row as i32;
+                bwt.push(0);
+            } else {
+                bwt.push(text[p - 1]);
+            }
+
+            let (k, rem) = (p / sample_rate as usize, p % sample_rate as usize);
+            if rem == 0 {
+                sampled[row >> 6] |= 1 << (row & 63);
+                sa_samples.push(p as u64);
+                isa_samples[k] = row as u64;
+            }
+        }
+
+        let mut c = vec![0i32; 257];
+        c[0] = 1;
+        for b in text.iter() {
+            c[*b as usize + 1] += 1;
+        }
+        for b in 1..257 {
+            c[b] += c[b - 1];
+        }
+
+        FmIndex {
+            bwt: WaveletTree::new(&bwt),
+            dollar,
+            c,
+            sampled: BM::new(sampled),
+            sa_samples: IntVec::from_values(&sa_samples),
+            isa_samples: IntVec::from_values(&isa_samples),
+            sample_rate,
+            len: n as i32,
+            key_starts: vec![],
+        }
+    }
+
+    /// Build over keys, such as the keys of a `StaticKV`, to find the keys containing a pattern.
+    ///
+    /// The keys are concatenated into one text.
+    #[allow(dead_code)]
+    pub fn from_keys(keys: &[Vec<u8>], sample_rate: i32) -> Self {
+        let mut text = vec![];
+        let mut key_starts = Vec::with_capacity(keys.len() + 1);
+
+        for k in keys.iter() {
+            key_starts.push(text.len() as i32);
+            text.extend_from_slice(k);
+        }
+        key_starts.push(text.len() as i32);
+
+        let mut fm = Self::new(&text, sample_rate);
+        fm.key_starts = key_starts;
+        fm
+    }
+}
+
+impl<BM> FmIndex<BM>
+where BM: BitmapOps
+{
+    /// Returns the number of occurrences of `pattern` in the text.
+    #[allow(dead_code)]
+    pub fn count(&self, pattern: &[u8]) -> i32 {
+        let (sp, ep) = self.backward_search(pattern);
+        ep - sp
+    }
+
+    /// Returns the start positions of all occurrences of `pattern` in the text, in no particular
+    /// order.
+    #[allow(dead_code)]
+    pub fn locate(&self, pattern: &[u8]) -> Vec<i32> {
+        let (sp, ep) = self.backward_search(pattern);
+        (sp..ep).map(|row| self.sa(row)).collect()
+    }
+
+    /// Returns the text in range `[from, to)`.
+    #[allow(dead_code)]
+    pub fn extract(&self, from: i32, to: i32) -> Vec<u8> {
+        assert!(
+            0 <= from && from <= to && to <= self.len,
+            "invalid range: [{}, {})",
+            from,
+            to
+        );
+
+        // Start from the first sampled position at or after `to` and walk backward.
+        let k = (to + self.sample_rate - 1) / self.sample_rate;
+        let (mut pos, mut row) = if k * self.sample_rate >= self.len {
+            // The empty suffix is always the first row.
+            (self.len, 0)
+        } else {
+            (
+                k * self.sample_rate,
+                self.isa_samples.get(k as usize) as i32,
+            )
+        };
+
+        let mut res = vec![];
+        while pos > from {
+            let b = self.bwt.access(row);
+            if pos <= to {
+                res.push(b);
+            }
+            row = self.lf(row, b);
+            pos -= 1;
+        }
+
+        res.reverse();
+        res
+    }
+
+    /// Returns the indexes of the keys containing `pattern`, in ascending order.
+    /// It must be built with `from_keys()`.
+    #[allow(dead_code)]
+    pub fn keys_containing(&self, pattern: &[u8]) -> Vec<i32> {
+        let keys = self.key_starts.len() as i32 - 1;
+
+        // An empty pattern matches every key, including empty ones.
+        if pattern.is_empty() {
+            return (0..keys).collect();
+        }
+
+        let plen = pattern.len() as i32;
+
+        let mut res = self
+            .locate(pattern)
+            .into_iter()
+            .filter_map(|p| {
+                let k = self.key_starts.partition_point(|s| *s <= p) as i32 - 1;
+
+                // A match spanning two keys is not a match.
+                if p + plen <= self.key_starts[k as usize + 1] {
+                    Some(k)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        res.sort_unstable();
+        res.dedup();
+        res
+    }
+
+    /// Returns the rows `[sp, ep)` of the suffixes starting with `pattern`.
+    fn backward_search(&self, pattern: &[u8]) -> (i32, i32) {
+        let (mut sp, mut ep) = (0, self.len + 1);
+
+        for b in pattern.iter().rev() {
+            sp = self.lf(sp, *b);
+            ep = self.lf(ep, *b);
+
+            if sp >= ep {
+                return (0, 0);
+            }
+        }
+
+        (sp, ep)
+    }
+
+    /// Returns the suffix array value at `row`, by walking with LF to a sampled row.
+    fn sa(&self, row: i32) -> i32 {
+        let mut row = row;
+        let mut steps = 0;
+
+        loop {
+            let (rank, bit) = self.sampled.count_ones(row);
+            if bit == 1 {
+                return self.sa_samples.get(rank as usize) as i32 + steps;
+            }
+
+            // The row of `$` has suffix 0, which is always sampled.
+            row = self.lf(row, self.bwt.access(row));
+            steps += 1;
+        }
+    }
+
+    /// Returns `C[b] + rank(b, i)`, i.e., the row of the suffix `b` followed by the suffix at `i`
+    /// if the BWT at `i` is `b`.
+    fn lf(&self, i: i32, b: u8) -> i32 {
+        let mut rank = self.bwt.rank(b, i);
+
+        // The `$` is stored as `0` but is not a `0`.
+        if b == 0 && self.dollar < i {
+            rank -= 1;
+        }
+
+        self.c[b as usize] + rank
+    }
+}
+
+/// Build the suffix array of `text` terminated by a `$`, by prefix doubling.
+///
+/// The suffix of `$` alone is the first element.
+fn suffix_array(text: &[u8]) -> Vec<i32> {
+    let n = text.len() + 1;
+
+    let mut sa = (0..n as i32).collect::<Vec<_>>();
+
+    // `$` ranks 0.
+    let mut rank = text.iter().map(|b| *b as i32 + 1).collect::<Vec<_>>();
+    rank.push(0);
+
+    let mut tmp = vec![0; n];
+    let mut k = 1;
+
+    loop {
+        let key = |i: i32| -> (i32, i32) {
+            let i = i as usize;
+            let second = if i + k < n { rank[i + k] } else { -1 };
+            (rank[i], second)
+        };
+
+        sa.sort_by_key(|i| key(*i));
+
+        tmp[sa[0] as usize] = 0;
+        for j in 1..n {
+            let inc = (key(sa[j - 1]) < key(sa[j])) as i32;
+            tmp[sa[j] as usize] = tmp[sa[j - 1] as usize] + inc;
+        }
+
+        std::mem::swap(&mut rank, &mut tmp);
+
+        if rank[sa[n - 1] as usize] == n as i32 - 1 {
+            break;
+        }
+        k *= 2;
+    }
+
+    sa
+}
@@ -0,0 +1,99 @@
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::fm_index::FmIndex;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::StaticKV;
+use crate::testing::lcg;
+
+fn naive_locate(text: &[u8], pattern: &[u8]) -> Vec<i32> {
+    (0..=text.len().saturating_sub(pattern.len()))
+        .filter(|i| text[*i..].starts_with(pattern))
+        .map(|i| i as i32)
+        .collect()
+}
+
+#[test]
+fn test_fm_index_example() -> anyhow::Result<()> {
+    let fm = FmIndex::<LabelBitmap>::new(b"abracadabra", 4);
+
+    assert_eq!(5, fm.count(b"a"));
+    assert_eq!(2, fm.count(b"abra"));
+    assert_eq!(0, fm.count(b"abc"));
+    assert_eq!(12, fm.count(b""));
+
+    let mut got = fm.locate(b"abra");
+    got.sort_unstable();
+    assert_eq!(vec![0, 7], got);
+
+    assert_eq!(b"cad".to_vec(), fm.extract(4, 7));
+    assert_eq!(b"abracadabra".to_vec(), fm.extract(0, 11));
+    assert_eq!(b"".to_vec(), fm.extract(3, 3));
+
+    Ok(())
+}
+
+#[test]
+fn test_fm_index() -> anyhow::Result<()> {
+    let mut rand = lcg(3);
+
+    // a small alphabet including 0, which must not be confused with `$`.
+    let text = (0..2000).map(|_| rand(4) as u8).collect::<Vec<_>>();
+
+    for sample_rate in [1, 5, 32] {
+        let fm = FmIndex::<LabelBitmap>::new(&text, sample_rate);
+        let rle = FmIndex::<RleBitmap>::new(&text, sample_rate);
+
+        for len in [1, 2, 5, 9] {
+            for start in (0..text.len() - len).step_by(97) {
+                let pattern = &text[start..start + len];
+                let want = naive_locate(&text, pattern);
+
+                let mut got = fm.locate(pattern);
+                got.sort_unstable();
+
+                assert_eq!(want.len() as i32, fm.count(pattern));
+                assert_eq!(want, got);
+                assert_eq!(want.len() as i32, rle.count(pattern));
+            }
+        }
+
+        for from in (0..text.len() as i32).step_by(113) {
+            for to in [from, from + 1, from + 50, text.len() as i32] {
+                let to = to.min(text.len() as i32);
+                assert_eq!(
+                    text[from as usize..to as usize].to_vec(),
+                    fm.extract(from, to),
+                    "extract({}, {})",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_fm_index_keys() -> anyhow::Result<()> {
+    let keys = ["", "apple", "banana", "bandana", "can", "nab"]
+        .iter()
+        .map(|k| k.as_bytes().to_vec())
+        .collect::<Vec<_>>();
+
+    let kv = StaticKV::<LabelBitmap>::new(&keys);
+    let fm = FmIndex::<LabelBitmap>::from_keys(&kv.keys(), 4);
+
+    assert_eq!(vec![2, 3], fm.keys_containing(b"ana"));
+    assert_eq!(vec![2, 3, 4], fm.keys_containing(b"an"));
+    assert_eq!(vec![5], fm.keys_containing(b"nab"));
+    assert_eq!(vec![1], fm.keys_containing(b"pp"));
+    assert_eq!(Vec::<i32>::new(), fm.keys_containing(b"ec"));
+
+    // "apple" + "banana": "eb" only occurs across two keys.
+    assert_eq!(1, fm.count(b"eb"));
+    assert_eq!(Vec::<i32>::new(), fm.keys_containing(b"eb"));
+
+    assert_eq!(6, fm.keys_containing(b"").len());
+
+    Ok(())
+}
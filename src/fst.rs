@@ -0,0 +1,276 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::static_kv::find_label;
+use crate::static_kv::get_bit;
+use crate::static_kv::node_range;
+use crate::static_kv::node_start;
+use crate::static_kv::set_bit;
+
+/// A fast succinct trie(FST), in which the upper levels are encoded in LOUDS-Dense and the lower
+/// levels in LOUDS-Sparse.
+///
+/// A label either leads to a child node, or it is the last byte of a key that no other key
+/// continues. A node is a prefix key if a key ends at it and other keys continue.
+/// Nodes are identified in breadth first order, and the i-th label that has a child leads to node
+/// `i + 1`, where the labels of dense nodes go before those of sparse nodes.
+///
+/// LOUDS-Dense, for the nodes above level `dense_levels`:
+/// - `d_labels` has 256 bits for every node, with a `1` for every label it has;
+/// - `d_has_child` has 256 bits for every node, with a `1` for every label that has a child;
+/// - `d_is_prefix` has 1 bit for every node.
+///
+/// LOUDS-Sparse, the same as `StaticKV`, for the other nodes, numbered from 0:
+/// - `s_labels` are the labels of all nodes;
+/// - `s_label_bitmap` has a `0` for every label and a `1` at the end of every node;
+/// - `s_has_child` has a bit for every label;
+/// - `s_is_prefix` has a bit for every node.
+pub struct Fst<BM>
+where BM: BitmapOps
+{
+    pub d_labels: BM,
+    pub d_has_child: BM,
+    pub d_is_prefix: Vec<u64>,
+
+    /// The number of dense nodes.
+    pub dense_nodes: i32,
+
+    /// The number of labels in dense nodes that have a child.
+    pub dense_children: i32,
+
+    /// The number of labels in dense nodes that do not have a child.
+    pub dense_terminals: i32,
+
+    pub s_labels: Vec<u8>,
+    pub s_label_bitmap: BM,
+    pub s_has_child: BM,
+    pub s_is_prefix: Vec<u64>,
+}
+
+/// The result of following a label from a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// The label leads to a child node.
+    Child(i32),
+
+    /// The label is the last byte of a key. It has the index of the label among all labels
+    /// without a child, in node order.
+    Terminal(i32),
+
+    /// The node has no such label.
+    Missing,
+}
+
+impl<BM> Fst<BM>
+where BM: BuildBitmap
+{
+    /// Build from a sorted list of keys, encoding the first `dense_levels` levels in LOUDS-Dense.
+    #[allow(dead_code)]
+    pub fn new(keys: &[Vec<u8>], dense_levels: usize) -> Self {
+        let mut d_labels = vec![];
+        let mut d_has_child = vec![];
+        let mut d_is_prefix = vec![];
+
+        let mut s_labels = vec![];
+        let mut s_label_bitmap = vec![];
+        let mut s_has_child = vec![];
+        let mut s_is_prefix = vec![];
+
+        let mut dense_nodes = 0;
+        let mut dense_children = 0;
+        let mut dense_terminals = 0;
+
+        // (start, end, col) of every node in breadth first order, `col` is the depth.
+        let mut queue = vec![(0, keys.len(), 0)];
+        let mut node_id = 0;
+
+        while node_id < queue.len() {
+            let (mut start, end, col) = queue[node_id];
+            let dense = col < dense_levels;
+
+            let is_prefix = start < end && keys[start].len() == col;
+            if is_prefix {
+                start += 1;
+            }
+
+            if dense {
+                set_bit(&mut d_is_prefix, node_id, is_prefix as u64);
+                dense_nodes += 1;
+            } else {
+                set_bit(&mut s_is_prefix, node_id - dense_nodes, is_prefix as u64);
+            }
+
+            let mut j = start;
+            while j < end {
+                let frm = j;
+                let label = keys[frm][col];
+
+                while j < end && keys[j][col] == label {
+                    j += 1;
+                }
+
+                // The only key ends at this label.
+                let has_child = !(j - frm == 1 && keys[frm].len() == col + 1);
+                if has_child {
+                    queue.push((frm, j, col + 1));
+                }
+
+                if dense {
+                    let i = node_id * 256 + label as usize;
+                    set_bit(&mut d_labels, i, 1);
+                    set_bit(&mut d_has_child, i, has_child as u64);
+                    dense_children += has_child as i32;
+                    dense_terminals += !has_child as i32;
+                } else {
+                    set_bit(&mut s_has_child, s_labels.len(), has_child as u64);
+                    set_bit(
+                        &mut s_label_bitmap,
+                        s_labels.len() + node_id - dense_nodes,
+                        0,
+                    );
+                    s_labels.push(label);
+                }
+            }
+
+            if !dense {
+                set_bit(
+                    &mut s_label_bitmap,
+                    s_labels.len() + node_id - dense_nodes,
+                    1,
+                );
+            }
+
+            node_id += 1;
+        }
+
+        let sparse_nodes = node_id - dense_nodes;
+
+        // One more word to make `count_ones(len)` valid.
+        pad(&mut d_labels, dense_nodes * 256);
+        pad(&mut d_has_child, dense_nodes * 256);
+        pad(&mut d_is_prefix, dense_nodes);
+        pad(&mut s_label_bitmap, s_labels.len() + sparse_nodes);
+        pad(&mut s_has_child, s_labels.len());
+        pad(&mut s_is_prefix, sparse_nodes);
+
+        Fst {
+            d_labels: BM::new(d_labels),
+            d_has_child: BM::new(d_has_child),
+            d_is_prefix,
+            dense_nodes: dense_nodes as i32,
+            dense_children,
+            dense_terminals,
+            s_labels,
+            s_label_bitmap: BM::new(s_label_bitmap),
+            s_has_child: BM::new(s_has_child),
+            s_is_prefix,
+        }
+    }
+}
+
+impl<BM> Fst<BM>
+where BM: BitmapOps
+{
+    #[allow(dead_code)]
+    pub fn has(&self, key: &[u8]) -> bool {
+        let mut node_id = 0;
+
+        for (i, c) in key.iter().enumerate() {
+            match self.step(node_id, *c) {
+                Step::Child(child) => node_id = child,
+                Step::Terminal(_) => return i + 1 == key.len(),
+                Step::Missing => return false,
+            }
+        }
+
+        self.is_prefix(node_id)
+    }
+
+    /// Follow label `c` from node `node_id`.
+    ///
+    /// A dense node checks the bit of `c`, and a sparse node searches its labels with
+    /// `find_label()`.
+    pub fn step(&self, node_id: i32, c: u8) -> Step {
+        if node_id < self.dense_nodes {
+            let i = node_id * 256 + c as i32;
+            return if self.d_labels.get_bit(i) == 0 {
+                Step::Missing
+            } else {
+                self.dense_step(i)
+            };
+        }
+
+        let s = node_id - self.dense_nodes;
+        let (start, end) = node_range(&self.s_label_bitmap, s);
+
+        match find_label(&self.s_labels, s, start, end, c) {
+            Some(bitmap_index) => self.sparse_step(bitmap_index - s),
+            None => Step::Missing,
+        }
+    }
+
+    /// Returns the smallest label of node `node_id` that is not less than `from`, and where it
+    /// leads to. `from` can be 256, for which there is no label.
+    pub fn next_label(&self, node_id: i32, from: u32) -> Option<(u8, Step)> {
+        if node_id < self.dense_nodes {
+            for c in from..256 {
+                let i = node_id * 256 + c as i32;
+
+                if self.d_labels.get_bit(i) != 0 {
+                    return Some((c as u8, self.dense_step(i)));
+                }
+            }
+            return None;
+        }
+
+        let s = node_id - self.dense_nodes;
+        let mut bitmap_index = node_start(&self.s_label_bitmap, s);
+
+        while self.s_label_bitmap.get_bit(bitmap_index) == 0 {
+            let label_index = bitmap_index - s;
+            let label = self.s_labels[label_index as usize];
+
+            if label as u32 >= from {
+                return Some((label, self.sparse_step(label_index)));
+            }
+
+            bitmap_index += 1;
+        }
+
+        None
+    }
+
+    /// Returns where the label at bit `i` of `d_labels` leads to.
+    fn dense_step(&self, i: i32) -> Step {
+        // A single rank gives the child.
+        let (rank, has_child) = self.d_has_child.count_ones(i);
+        if has_child == 1 {
+            Step::Child(rank + 1)
+        } else {
+            Step::Terminal(self.d_labels.count_ones(i).0 - rank)
+        }
+    }
+
+    /// Returns where the `label_index`-th sparse label leads to.
+    fn sparse_step(&self, label_index: i32) -> Step {
+        let (rank, has_child) = self.s_has_child.count_ones(label_index);
+        if has_child == 1 {
+            Step::Child(self.dense_children + rank + 1)
+        } else {
+            Step::Terminal(self.dense_terminals + label_index - rank)
+        }
+    }
+
+    /// Returns if a key ends at node `node_id` and other keys continue.
+    pub fn is_prefix(&self, node_id: i32) -> bool {
+        if node_id < self.dense_nodes {
+            get_bit(&self.d_is_prefix, node_id as usize) != 0
+        } else {
+            get_bit(&self.s_is_prefix, (node_id - self.dense_nodes) as usize) != 0
+        }
+    }
+}
+
+/// Extend `words` to hold `len` bits and one more word.
+fn pad(words: &mut Vec<u64>, len: usize) {
+    words.resize((len >> 6) + 1, 0);
+}
@@ -0,0 +1,118 @@
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::fst::Fst;
+use crate::fst::Step;
+use crate::static_kv::LabelBitmap;
+use crate::testing::lcg;
+
+fn to_vecs(v: &[&str]) -> Vec<Vec<u8>> {
+    v.iter().map(|x| x.as_bytes().to_vec()).collect()
+}
+
+#[test]
+fn test_fst_example() -> anyhow::Result<()> {
+    let keys = to_vecs(&["a", "ab", "abc", "b", "bcd", "bce"]);
+
+    // root(dense): a -> 1, b -> 2
+    // 1(dense, prefix key): b -> 3
+    // 2(dense, prefix key): c -> 4
+    // 3(sparse, prefix key): c, terminal
+    // 4(sparse): d, e, terminal
+    let fst = Fst::<LabelBitmap>::new(&keys, 2);
+
+    assert_eq!(3, fst.dense_nodes);
+    assert_eq!(4, fst.dense_children);
+    assert_eq!(0, fst.dense_terminals);
+    assert_eq!(b"cde".to_vec(), fst.s_labels);
+
+    assert_eq!(Step::Child(2), fst.step(0, b'b'));
+    assert_eq!(Step::Child(4), fst.step(2, b'c'));
+    assert_eq!(Step::Terminal(0), fst.step(3, b'c'));
+    assert_eq!(Step::Terminal(2), fst.step(4, b'e'));
+    assert_eq!(Some((b'd', Step::Terminal(1))), fst.next_label(4, 0));
+    assert_eq!(
+        Some((b'b', Step::Child(2))),
+        fst.next_label(0, b'a' as u32 + 1)
+    );
+    assert_eq!(None, fst.next_label(0, b'b' as u32 + 1));
+    assert_eq!(Step::Missing, fst.step(4, b'f'));
+    assert!(fst.is_prefix(3));
+    assert!(!fst.is_prefix(4));
+
+    for k in keys.iter() {
+        assert!(fst.has(k));
+    }
+    for k in ["", "abcd", "bc", "bcf", "c", "ac"] {
+        assert!(!fst.has(k.as_bytes()), "absent key: {}", k);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_fst() -> anyhow::Result<()> {
+    let mut rand = lcg(11);
+
+    let mut keys = (0..2000)
+        .map(|_| {
+            let len = rand(8);
+            (0..len).map(|_| b'a' + rand(6) as u8).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    keys.push(vec![]);
+    keys.push(vec![0, 255]);
+    keys.sort();
+    keys.dedup();
+
+    let probes = (0..2000)
+        .map(|_| {
+            let len = rand(9);
+            (0..len).map(|_| b'a' + rand(7) as u8).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    for dense_levels in [0, 1, 3, 100] {
+        let fst = Fst::<LabelBitmap>::new(&keys, dense_levels);
+        let rle = Fst::<RleBitmap>::new(&keys, dense_levels);
+
+        for k in keys.iter() {
+            assert!(fst.has(k), "key: {:?}", k);
+            assert!(rle.has(k), "key: {:?}", k);
+        }
+
+        for k in probes.iter() {
+            let want = keys.binary_search(k).is_ok();
+            assert_eq!(want, fst.has(k), "probe: {:?}", k);
+        }
+    }
+
+    let fst = Fst::<LabelBitmap>::new(&[], 2);
+    assert!(!fst.has(b""));
+
+    Ok(())
+}
+
+#[test]
+fn test_fst_wide_sparse_node() -> anyhow::Result<()> {
+    // Sparse nodes with more labels than a word of `find_byte()`.
+    let mut keys = vec![];
+    for a in (b'0'..b'z').step_by(2) {
+        for b in (b'0'..b'z').step_by(3) {
+            keys.push(vec![a, b]);
+        }
+    }
+
+    for dense_levels in [0, 1] {
+        let fst = Fst::<LabelBitmap>::new(&keys, dense_levels);
+
+        for a in b'0'..=b'z' {
+            for b in b'0'..=b'z' {
+                let k = vec![a, b];
+                let want = keys.binary_search(&k).is_ok();
+                assert_eq!(want, fst.has(&k), "key: {:?}", k);
+            }
+            assert!(!fst.has(&[a]));
+        }
+    }
+
+    Ok(())
+}
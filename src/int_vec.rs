@@ -0,0 +1,130 @@
+use crate::bitmap::context::Context;
+use crate::static_kv::CTX;
+
+/// A vector of integers packed with a fixed bit width from 1 to 64.
+///
+/// The i-th value occupies bits `[i * width, (i + 1) * width)`, and may span two words.
+#[derive(Clone)]
+pub struct IntVec {
+    pub words: Vec<u64>,
+
+    /// The number of bits of every value.
+    pub width: u32,
+
+    /// The number of values.
+    pub len: usize,
+
+    pub ctx: &'static Context,
+}
+
+impl IntVec {
+    /// Create an empty vector storing values of `width` bits.
+    #[allow(dead_code)]
+    pub fn new(width: u32) -> Self {
+        assert!(
+            (1..=64).contains(&width),
+            "width must be in [1, 64]: {}",
+            width
+        );
+
+        IntVec {
+            words: vec![],
+            width,
+            len: 0,
+            ctx: &CTX,
+        }
+    }
+
+    /// Build with the least number of bits that can represent every value.
+    #[allow(dead_code)]
+    pub fn from_values(values: &[u64]) -> Self {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let width = (64 - max.leading_zeros()).max(1);
+
+        let mut v = Self::new(width);
+        v.words.reserve((values.len() * width as usize + 63) >> 6);
+
+        for x in values.iter() {
+            v.push(*x);
+        }
+
+        v
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the value at `i`.
+    #[allow(dead_code)]
+    pub fn get(&self, i: usize) -> u64 {
+        assert!(i < self.len, "index out of range: {} >= {}", i, self.len);
+
+        let width = self.width as usize;
+        let (w, o) = self.locate(i);
+
+        let mut v = self.words[w] >> o;
+        if o + width > 64 {
+            v |= self.words[w + 1] << (64 - o);
+        }
+
+        v & self.ctx.masks.mask[width]
+    }
+
+    /// Set the value at `i`. `v` must fit in `width` bits.
+    #[allow(dead_code)]
+    pub fn set(&mut self, i: usize, v: u64) {
+        assert!(i < self.len, "index out of range: {} >= {}", i, self.len);
+
+        let masks = &self.ctx.masks;
+        let width = self.width as usize;
+
+        debug_assert!(
+            v & masks.r_mask[width] == 0,
+            "value {} does not fit in {} bits",
+            v,
+            width
+        );
+
+        let (w, o) = self.locate(i);
+
+        self.words[w] &= !(masks.mask[width] << o);
+        self.words[w] |= v << o;
+
+        if o + width > 64 {
+            // The upper part goes to the lower bits of the next word.
+            let rest = o + width - 64;
+            self.words[w + 1] &= masks.r_mask[rest];
+            self.words[w + 1] |= v >> (64 - o);
+        }
+    }
+
+    /// Append a value. `v` must fit in `width` bits.
+    #[allow(dead_code)]
+    pub fn push(&mut self, v: u64) {
+        let bits = (self.len + 1) * self.width as usize;
+        while self.words.len() << 6 < bits {
+            self.words.push(0);
+        }
+
+        self.len += 1;
+        self.set(self.len - 1, v);
+    }
+
+    #[allow(dead_code)]
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+
+    /// Returns the word index and the bit offset in the word of the i-th value.
+    fn locate(&self, i: usize) -> (usize, usize) {
+        let bit = i * self.width as usize;
+        (bit >> 6, bit & 63)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::int_vec::IntVec;
+
+#[test]
+fn test_int_vec_example() -> anyhow::Result<()> {
+    let mut v = IntVec::new(3);
+    assert!(v.is_empty());
+
+    for x in [1, 7, 0, 5] {
+        v.push(x);
+    }
+
+    assert_eq!(4, v.len());
+    assert_eq!(vec![1, 7, 0, 5], v.iter().collect::<Vec<_>>());
+    assert_eq!(1, v.words.len());
+
+    v.set(1, 2);
+    assert_eq!(vec![1, 2, 0, 5], v.iter().collect::<Vec<_>>());
+
+    let v = IntVec::from_values(&[3, 100, 0]);
+    assert_eq!(7, v.width);
+    assert_eq!(100, v.get(1));
+
+    Ok(())
+}
+
+#[test]
+fn test_int_vec_all_widths() -> anyhow::Result<()> {
+    let n = 200;
+
+    for width in 1..=64u32 {
+        let max = if width == 64 {
+            u64::MAX
+        } else {
+            (1 << width) - 1
+        };
+
+        // values that fill every bit pattern, including across word boundaries
+        let values = (0..n as u64)
+            .map(|i| i.wrapping_mul(0x9e3779b97f4a7c15) & max)
+            .collect::<Vec<_>>();
+
+        let mut v = IntVec::new(width);
+        for x in values.iter() {
+            v.push(*x);
+        }
+
+        assert_eq!(n, v.len());
+        assert_eq!((n * width as usize + 63) >> 6, v.words.len());
+        assert_eq!(values, v.iter().collect::<Vec<_>>(), "width: {}", width);
+
+        // set must not disturb the neighbours
+        for i in (0..n).step_by(3) {
+            v.set(i, max - values[i]);
+        }
+        for (i, x) in values.iter().enumerate() {
+            let want = if i % 3 == 0 { max - x } else { *x };
+            assert_eq!(want, v.get(i), "width: {}, i: {}", width, i);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_int_vec_get_out_of_range() {
+    let v = IntVec::from_values(&[1, 2]);
+    v.get(2);
+}
@@ -0,0 +1,193 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+
+/// A k²-tree, a compressed adjacency matrix of a directed graph.
+///
+/// The `side x side` matrix is split into `k x k` submatrices, recursively, until a submatrix is a
+/// single cell. Every submatrix is a tree node with a bit: `1` if it has any edge. The children of
+/// a node are ordered by row then by column.
+///
+/// The bits of all levels but the last are stored in `tree` in breadth first order, and the last
+/// level in `leaves`. The children of the `1` at position `x` in `tree` start at
+/// `count_ones(x + 1) * k * k` in the concatenation of `tree` and `leaves`.
+pub struct K2Tree<BM>
+where BM: BitmapOps
+{
+    pub tree: BM,
+    pub leaves: BM,
+
+    /// The number of bits in `tree`.
+    pub tree_len: i32,
+
+    pub k: i32,
+
+    /// The side of the matrix, a power of `k`.
+    pub side: i32,
+
+    /// The number of vertices.
+    pub n: i32,
+}
+
+impl<BM> K2Tree<BM>
+where BM: BuildBitmap
+{
+    /// Build from the edges of a graph of `n` vertices `[0, n)`.
+    #[allow(dead_code)]
+    pub fn new(n: i32, edges: &[(i32, i32)], k: i32) -> Self {
+        assert!(k >= 2, "k must be at least 2: {}", k);
+
+        let mut side = k;
+        while side < n {
+            side *= k;
+        }
+
+        let mut edges = edges.to_vec();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut levels: Vec<Vec<bool>> = vec![];
+
+        // The submatrices at the current level: (row, col, edges in it).
+        let mut cur = vec![(0, 0, edges)];
+        let mut size = side;
+
+        while size > 1 {
+            let s = size / k;
+            let mut bits = vec![];
+            let mut next = vec![];
+
+            for (row, col, es) in cur {
+                let mut children = vec![vec![]; (k * k) as usize];
+                for (u, v) in es {
+                    let i = (u - row) / s;
+                    let j = (v - col) / s;
+                    children[(i * k + j) as usize].push((u, v));
+                }
+
+                for (c, es) in children.into_iter().enumerate() {
+                    let c = c as i32;
+                    bits.push(!es.is_empty());
+
+                    if !es.is_empty() {
+                        next.push((row + c / k * s, col + c % k * s, es));
+                    }
+                }
+            }
+
+            levels.push(bits);
+            cur = next;
+            size = s;
+        }
+
+        let leaves = levels.pop().unwrap();
+        let tree = levels.concat();
+
+        K2Tree {
+            tree: BM::new(to_words(&tree)),
+            leaves: BM::new(to_words(&leaves)),
+            tree_len: tree.len() as i32,
+            k,
+            side,
+            n,
+        }
+    }
+}
+
+impl<BM> K2Tree<BM>
+where BM: BitmapOps
+{
+    /// Returns if there is an edge from `u` to `v`.
+    #[allow(dead_code)]
+    pub fn has_edge(&self, u: i32, v: i32) -> bool {
+        !self.range(u, u + 1, v, v + 1).is_empty()
+    }
+
+    /// Returns the vertices that `u` has an edge to, in ascending order.
+    #[allow(dead_code)]
+    pub fn successors(&self, u: i32) -> Vec<i32> {
+        self.range(u, u + 1, 0, self.n)
+            .into_iter()
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    /// Returns the vertices that have an edge to `v`, in ascending order.
+    #[allow(dead_code)]
+    pub fn predecessors(&self, v: i32) -> Vec<i32> {
+        self.range(0, self.n, v, v + 1)
+            .into_iter()
+            .map(|(u, _)| u)
+            .collect()
+    }
+
+    /// Returns all edges `(u, v)` with `u` in `[from_u, to_u)` and `v` in `[from_v, to_v)`, ordered
+    /// by the position in the tree.
+    #[allow(dead_code)]
+    pub fn range(&self, from_u: i32, to_u: i32, from_v: i32, to_v: i32) -> Vec<(i32, i32)> {
+        let mut res = vec![];
+        let rect = (
+            from_u.max(0),
+            to_u.min(self.n),
+            from_v.max(0),
+            to_v.min(self.n),
+        );
+
+        if rect.0 < rect.1 && rect.2 < rect.3 {
+            self.walk(0, self.side, 0, 0, rect, &mut res);
+        }
+
+        res
+    }
+
+    /// Visit the children of a node at `(row, col)` of side `size`, whose children start at `z`,
+    /// and collect edges in `rect` into `res`.
+    fn walk(
+        &self,
+        z: i32,
+        size: i32,
+        row: i32,
+        col: i32,
+        rect: (i32, i32, i32, i32),
+        res: &mut Vec<(i32, i32)>,
+    ) {
+        let s = size / self.k;
+        let (from_u, to_u, from_v, to_v) = rect;
+
+        for i in 0..self.k {
+            let r = row + i * s;
+            if r + s <= from_u || r >= to_u {
+                continue;
+            }
+
+            for j in 0..self.k {
+                let c = col + j * s;
+                if c + s <= from_v || c >= to_v {
+                    continue;
+                }
+
+                let p = z + i * self.k + j;
+                if p >= self.tree_len {
+                    if self.leaves.get_bit(p - self.tree_len) == 1 {
+                        res.push((r, c));
+                    }
+                } else if self.tree.get_bit(p) == 1 {
+                    let children = self.tree.count_ones(p + 1).0 * self.k * self.k;
+                    self.walk(children, s, r, c, rect, res);
+                }
+            }
+        }
+    }
+}
+
+fn to_words(bits: &[bool]) -> Vec<u64> {
+    // One more word to make `count_ones(len)` valid.
+    let mut words = vec![0u64; (bits.len() >> 6) + 1];
+
+    for (i, b) in bits.iter().enumerate() {
+        if *b {
+            words[i >> 6] |= 1 << (i & 63);
+        }
+    }
+
+    words
+}
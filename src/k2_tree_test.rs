@@ -0,0 +1,87 @@
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::k2_tree::K2Tree;
+use crate::static_kv::LabelBitmap;
+use crate::testing::lcg;
+
+#[test]
+fn test_k2_tree_example() -> anyhow::Result<()> {
+    let edges = vec![(0, 1), (1, 2), (1, 3), (3, 0), (4, 4)];
+    let t = K2Tree::<LabelBitmap>::new(5, &edges, 2);
+
+    assert_eq!(8, t.side);
+    assert!(t.has_edge(1, 3));
+    assert!(t.has_edge(4, 4));
+    assert!(!t.has_edge(3, 1));
+    assert!(!t.has_edge(2, 2));
+
+    assert_eq!(vec![2, 3], t.successors(1));
+    assert_eq!(Vec::<i32>::new(), t.successors(2));
+    assert_eq!(vec![0], t.predecessors(1));
+    assert_eq!(vec![3], t.predecessors(0));
+
+    let mut got = t.range(0, 2, 1, 5);
+    got.sort_unstable();
+    assert_eq!(vec![(0, 1), (1, 2), (1, 3)], got);
+
+    Ok(())
+}
+
+#[test]
+fn test_k2_tree() -> anyhow::Result<()> {
+    let mut rng = lcg(5);
+    let mut rand = |m: u64| -> i32 { rng(m) as i32 };
+
+    for (n, m, k) in [
+        (1, 1, 2),
+        (10, 0, 2),
+        (50, 200, 2),
+        (100, 300, 3),
+        (200, 2000, 4),
+    ] {
+        let edges = (0..m)
+            .map(|_| (rand(n as u64), rand(n as u64)))
+            .collect::<Vec<_>>();
+
+        let t = K2Tree::<LabelBitmap>::new(n, &edges, k);
+        let rle = K2Tree::<RleBitmap>::new(n, &edges, k);
+
+        let mut matrix = vec![vec![false; n as usize]; n as usize];
+        for (u, v) in edges.iter() {
+            matrix[*u as usize][*v as usize] = true;
+        }
+
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(matrix[u as usize][v as usize], t.has_edge(u, v));
+            }
+
+            let want = (0..n)
+                .filter(|v| matrix[u as usize][*v as usize])
+                .collect::<Vec<_>>();
+            assert_eq!(want, t.successors(u), "successors({})", u);
+            assert_eq!(want, rle.successors(u), "successors({})", u);
+
+            let want = (0..n)
+                .filter(|w| matrix[*w as usize][u as usize])
+                .collect::<Vec<_>>();
+            assert_eq!(want, t.predecessors(u), "predecessors({})", u);
+        }
+
+        for (fu, tu, fv, tv) in [(0, n, 0, n), (n / 3, n / 2, n / 4, n), (n - 1, n, 0, n)] {
+            let mut got = t.range(fu, tu, fv, tv);
+            got.sort_unstable();
+
+            let mut want = vec![];
+            for u in fu..tu {
+                for v in fv..tv {
+                    if matrix[u as usize][v as usize] {
+                        want.push((u, v));
+                    }
+                }
+            }
+            assert_eq!(want, got);
+        }
+    }
+
+    Ok(())
+}
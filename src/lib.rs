@@ -1,5 +1,56 @@
+mod aho_corasick;
+#[cfg(test)]
+mod aho_corasick_test;
+mod automaton;
+#[cfg(test)]
+mod automaton_test;
 mod bitmap;
+mod codec;
+#[cfg(test)]
+mod codec_test;
+mod dac;
+#[cfg(test)]
+mod dac_test;
+mod fm_index;
+#[cfg(test)]
+mod fm_index_test;
+mod fst;
+#[cfg(test)]
+mod fst_test;
+mod k2_tree;
+#[cfg(test)]
+mod k2_tree_test;
+mod int_vec;
+#[cfg(test)]
+mod int_vec_test;
 mod static_kv;
+mod static_kv_file;
+mod static_kv_ref;
+mod suffix_kv;
+mod surf;
+mod tail_store;
+#[cfg(test)]
+mod testing;
+mod tree;
+mod wavelet_matrix;
+mod wavelet_tree;
+mod weighted_kv;
 
 #[cfg(test)]
 mod static_kv_test;
+#[cfg(test)]
+mod static_kv_file_test;
+#[cfg(test)]
+mod static_kv_ref_test;
+#[cfg(test)]
+mod suffix_kv_test;
+#[cfg(test)]
+mod surf_test;
+#[cfg(test)]
+mod tail_store_test;
+#[cfg(test)]
+mod wavelet_matrix_test;
+#[cfg(test)]
+mod wavelet_tree_test;
+#[cfg(test)]
+mod weighted_kv_test;
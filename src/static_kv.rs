@@ -1,10 +1,17 @@
+use std::borrow::Cow;
+
 use lazy_static::lazy_static;
 
+use crate::automaton::Automaton;
 use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
 use crate::bitmap::context::Context;
 use crate::bitmap::indexed_bitmap::IndexedBitmap;
 use crate::bitmap::rank::RankIndex64;
 use crate::bitmap::select::SelectIndex32;
+use crate::int_vec::IntVec;
+use crate::tail_store::TailStore;
+use crate::tree::louds::LoudsTree;
 
 lazy_static! {
     pub static ref CTX: Context = Context::new();
@@ -12,12 +19,102 @@ lazy_static! {
 
 pub type LabelBitmap = IndexedBitmap<SelectIndex32<RankIndex64>>;
 
-pub struct StaticKV<LBM>
-where LBM: BitmapOps
+/// A trie of static keys, in LOUDS form.
+///
+/// `L` and `B` are the storages of `leaves` and `labels`. They are owned by default, and can be
+/// borrowed to use a trie in place, such as a memory-mapped file: see `StaticKVRef`.
+///
+/// Built with `BuildOptions::tails`, an edge is labeled with a byte in `labels` followed by the
+/// rest of its path, the tail of the child, in `tails`.
+///
+/// Built with `BuildOptions::normalize`, keys are stored normalized and `has()`, `find_node()`,
+/// `keys_with_prefix()` normalize the query the same way. Other queries work on the stored keys.
+pub struct StaticKV<LBM, L = Vec<u64>, B = Vec<u8>>
+where
+    LBM: BitmapOps,
+    L: AsRef<[u64]>,
+    B: AsRef<[u8]>,
 {
-    pub leaves: Vec<u64>,
+    pub leaves: L,
     pub label_bitmap: LBM,
-    pub labels: Vec<u8>,
+    pub labels: B,
+
+    /// The tail of the edge leading to every node `v > 0`, as the string of edge `v - 1`.
+    pub tails: Option<TailStore<LBM>>,
+
+    /// The key normalization and the original spelling of every key.
+    pub originals: Option<Originals<LBM>>,
+}
+
+/// Options to build a `StaticKV`.
+#[derive(Clone, Debug, Default)]
+pub struct BuildOptions {
+    /// Path compression: a chain of nodes with one child and no key is collapsed into one edge,
+    /// including the unique suffix of a key. The first byte of an edge is a label and the rest is
+    /// kept in a `TailStore`.
+    ///
+    /// It saves a node and a label per byte of a chain, e.g., most of the nodes of a set of URLs.
+    pub tails: bool,
+
+    /// Key normalization: every byte of a key is mapped with it when built and when looked up,
+    /// e.g., ASCII case folding for case-insensitive keys. Keys that are the same after mapping
+    /// are stored once, and the first spelling is kept: see `StaticKV::original()`.
+    pub normalize: Option<ByteMap>,
+}
+
+/// A byte to byte mapping to normalize keys.
+#[derive(Clone, Debug)]
+pub struct ByteMap(pub [u8; 256]);
+
+impl ByteMap {
+    /// Build from a function mapping every byte.
+    #[allow(dead_code)]
+    pub fn new<F: Fn(u8) -> u8>(f: F) -> Self {
+        let mut m = [0u8; 256];
+        for (b, x) in m.iter_mut().enumerate() {
+            *x = f(b as u8);
+        }
+        ByteMap(m)
+    }
+
+    /// ASCII case folding: `A-Z` are mapped to `a-z`.
+    #[allow(dead_code)]
+    pub fn ascii_lowercase() -> Self {
+        Self::new(|b| b.to_ascii_lowercase())
+    }
+
+    pub fn apply(&self, key: &[u8]) -> Vec<u8> {
+        key.iter().map(|b| self.0[*b as usize]).collect()
+    }
+}
+
+/// The original spelling of the keys of a trie built with `BuildOptions::normalize`.
+///
+/// `key_nodes` has a `1` at every key node, and the spelling of such a node `v` is the
+/// `count_ones(v)`-th one, in `bytes[starts[i]..starts[i + 1]]`.
+pub struct Originals<LBM>
+where LBM: BitmapOps
+{
+    pub map: ByteMap,
+    pub key_nodes: LBM,
+    pub bytes: Vec<u8>,
+    pub starts: IntVec,
+}
+
+impl<LBM> Originals<LBM>
+where LBM: BitmapOps
+{
+    /// Returns the original spelling of the key at node `node_id`, or `None` if it is not a key.
+    pub fn get(&self, node_id: i32) -> Option<&[u8]> {
+        let (i, is_key) = self.key_nodes.count_ones(node_id);
+        if is_key == 0 {
+            return None;
+        }
+
+        let start = self.starts.get(i as usize) as usize;
+        let end = self.starts.get(i as usize + 1) as usize;
+        Some(&self.bytes[start..end])
+    }
 }
 
 /// The breadth first walking state node
@@ -44,19 +141,75 @@ impl BFSNode {
 }
 
 impl<LBM> StaticKV<LBM>
-where LBM: BitmapOps
+where LBM: BuildBitmap
 {
     /// Build a compacted trie from a sorted list of keys.
     #[allow(dead_code)]
     pub fn new(keys: &[Vec<u8>]) -> Self {
+        Self::with_options(keys, &BuildOptions::default())
+    }
+
+    /// Build a compacted trie from a sorted list of keys with `opts`.
+    ///
+    /// With `BuildOptions::normalize`, keys can be in any order.
+    #[allow(dead_code)]
+    pub fn with_options(keys: &[Vec<u8>], opts: &BuildOptions) -> Self {
+        let map = match &opts.normalize {
+            None => return Self::build(keys, opts.tails),
+            Some(map) => map,
+        };
+
+        // A stable sort keeps the first spelling of a normalized key first.
+        let mut normalized = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (map.apply(k), i))
+            .collect::<Vec<_>>();
+        normalized.sort_by(|a, b| a.0.cmp(&b.0));
+        normalized.dedup_by(|a, b| a.0 == b.0);
+
+        let norm_keys = normalized
+            .iter()
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        let mut kv = Self::build(&norm_keys, opts.tails);
+
+        // Key nodes are in breadth first order, which is not the key order.
+        let mut spellings = normalized
+            .iter()
+            .map(|(k, i)| (kv.find_node(k).unwrap(), *i))
+            .collect::<Vec<_>>();
+        spellings.sort_unstable();
+
+        let mut bytes = vec![];
+        let mut starts = vec![0];
+        for (_, i) in spellings {
+            bytes.extend_from_slice(&keys[i]);
+            starts.push(bytes.len() as u64);
+        }
+
+        // One more word to make `count_ones(len)` valid.
+        let mut key_nodes = kv.leaves.clone();
+        key_nodes.resize(((kv.labels.len() + 1) >> 6) + 1, 0);
+
+        kv.originals = Some(Originals {
+            map: map.clone(),
+            key_nodes: LBM::new(key_nodes),
+            bytes,
+            starts: IntVec::from_values(&starts),
+        });
+        kv
+    }
+
+    fn build(keys: &[Vec<u8>], with_tails: bool) -> Self {
         let mut leaves = vec![];
-        let mut label_bitmap = vec![];
+        let mut degrees = vec![];
         let mut labels = vec![];
+        let mut tails: Vec<&[u8]> = vec![];
 
         let mut queue = vec![BFSNode::new(0, keys.len(), 0)];
 
         let mut node_id = 0;
-        let mut bitmap_index = 0;
 
         while node_id < queue.len() {
             let (mut start, end, col) = queue[node_id].unpack();
@@ -68,6 +221,7 @@ where LBM: BitmapOps
             }
 
             let mut j = start;
+            let mut degree = 0;
 
             while j < end {
                 let frm = j;
@@ -79,57 +233,429 @@ where LBM: BitmapOps
 
                 // new label
 
-                queue.push(BFSNode::new(frm, j, col + 1));
+                // The edge extends to the longest common prefix of the keys below it.
+                let mut edge_end = col + 1;
+                if with_tails {
+                    edge_end += common_prefix_len(&keys[frm][col + 1..], &keys[j - 1][col + 1..]);
+                    tails.push(&keys[frm][col + 1..edge_end]);
+                }
+
+                queue.push(BFSNode::new(frm, j, edge_end));
                 labels.push(label);
-                set_bit(&mut label_bitmap, bitmap_index, 0);
-                bitmap_index += 1;
+                degree += 1;
             }
 
-            set_bit(&mut label_bitmap, bitmap_index, 1);
-            bitmap_index += 1;
-
+            degrees.push(degree);
             node_id += 1;
         }
 
         StaticKV {
             leaves,
-            label_bitmap: LBM::new(label_bitmap),
+            label_bitmap: LoudsTree::<LBM>::from_degrees(&degrees).bitmap,
             labels,
+            tails: if with_tails {
+                Some(TailStore::new(&tails))
+            } else {
+                None
+            },
+            originals: None,
         }
     }
 }
 
-impl StaticKV<LabelBitmap> {
+impl<LBM, L, B> StaticKV<LBM, L, B>
+where
+    LBM: BitmapOps,
+    L: AsRef<[u64]>,
+    B: AsRef<[u8]>,
+{
     #[allow(dead_code)]
     pub fn has(&self, key: &[u8]) -> bool {
+        match self.find_node(key) {
+            Some(node_id) => get_bit(self.leaves.as_ref(), node_id as usize) != 0,
+            None => false,
+        }
+    }
+
+    /// Returns the node at the end of path `key`, or `None` if there is no such path.
+    /// The node is not necessarily a key. If the trie has tails, a path ending inside an edge is
+    /// not at any node.
+    #[allow(dead_code)]
+    pub fn find_node(&self, key: &[u8]) -> Option<i32> {
+        let key = self.normalize(key);
+        match self.walk(&key)? {
+            (node_id, len) if len == key.len() => Some(node_id),
+            _ => None,
+        }
+    }
+
+    /// Returns all keys starting with `prefix`, in sorted order.
+    /// With `BuildOptions::normalize` they are the normalized keys: see `originals_with_prefix()`.
+    #[allow(dead_code)]
+    pub fn keys_with_prefix(&self, prefix: &[u8]) -> Vec<Vec<u8>> {
+        let prefix = self.normalize(prefix);
+        let mut keys = vec![];
+
+        if let Some((node_id, len)) = self.walk(&prefix) {
+            // `prefix` may end inside the tail of the last edge.
+            let tail = self.tail(node_id);
+            let mut path = prefix.to_vec();
+            path.extend_from_slice(&tail[tail.len() + prefix.len() - len..]);
+
+            self.collect_keys(node_id, &mut path, &mut keys);
+        }
+
+        keys
+    }
+
+    /// Returns the original spelling of `key`, or `None` if it is not a key or the trie is built
+    /// without `BuildOptions::normalize`.
+    #[allow(dead_code)]
+    pub fn original(&self, key: &[u8]) -> Option<&[u8]> {
+        let originals = self.originals.as_ref()?;
+        originals.get(self.find_node(key)?)
+    }
+
+    /// Returns the original spelling of all keys starting with `prefix`, in the order of the
+    /// normalized keys. It is empty if the trie is built without `BuildOptions::normalize`.
+    #[allow(dead_code)]
+    pub fn originals_with_prefix(&self, prefix: &[u8]) -> Vec<&[u8]> {
+        let mut res = vec![];
+
+        if let Some(originals) = &self.originals {
+            if let Some((node_id, _)) = self.walk(&originals.map.apply(prefix)) {
+                self.collect_originals(originals, node_id, &mut res);
+            }
+        }
+        res
+    }
+
+    fn collect_originals<'a>(
+        &self,
+        originals: &'a Originals<LBM>,
+        node_id: i32,
+        res: &mut Vec<&'a [u8]>,
+    ) {
+        res.extend(originals.get(node_id));
+
+        for (_, child) in self.children(node_id) {
+            self.collect_originals(originals, child, res);
+        }
+    }
+
+    /// Returns `key` mapped with `BuildOptions::normalize`, or `key` itself if the trie is built
+    /// without it.
+    fn normalize<'a>(&self, key: &'a [u8]) -> Cow<'a, [u8]> {
+        match &self.originals {
+            Some(originals) => Cow::Owned(originals.map.apply(key)),
+            None => Cow::Borrowed(key),
+        }
+    }
+
+    /// Walk down from the root along `key`, and returns the node reached and the length of the
+    /// path to it. The path is longer than `key` if `key` ends inside the tail of the last edge.
+    /// It returns `None` if `key` is not a prefix of any path.
+    fn walk(&self, key: &[u8]) -> Option<(i32, usize)> {
         let mut node_id: i32 = 0;
-        let mut bitmap_index: i32 = 0;
 
-        for c in key {
-            loop {
-                if get_bit(&self.label_bitmap.words, bitmap_index as usize) != 0 {
-                    // no more labels in this node
-                    return false;
+        let mut i = 0;
+
+        while i < key.len() {
+            // go to next level
+            node_id = self.child(node_id, key[i])?;
+            i += 1;
+
+            let tail = self.tail(node_id);
+            let n = tail.len().min(key.len() - i);
+            if tail[..n] != key[i..i + n] {
+                return None;
+            }
+            i += tail.len();
+        }
+
+        Some((node_id, i))
+    }
+
+    /// Returns the tail of the edge leading to node `node_id`: the bytes of the edge after its
+    /// label. It is always empty if the trie is built without tails.
+    #[allow(dead_code)]
+    pub fn tail(&self, node_id: i32) -> &[u8] {
+        match &self.tails {
+            Some(tails) if node_id > 0 => tails.get(node_id - 1),
+            _ => &[],
+        }
+    }
+
+    /// Returns the child of node `node_id` by label `c`, or `None` if there is no such label.
+    #[allow(dead_code)]
+    pub fn child(&self, node_id: i32, c: u8) -> Option<i32> {
+        let (start, end) = node_range(&self.label_bitmap, node_id);
+        let bitmap_index = find_label(self.labels.as_ref(), node_id, start, end, c)?;
+
+        Some(self.label_bitmap.count_zeros(bitmap_index + 1).0)
+    }
+
+    /// Returns an iterator of the labels of node `node_id` and the children they lead to.
+    #[allow(dead_code)]
+    pub fn children(&self, node_id: i32) -> Children<'_, LBM> {
+        Children {
+            label_bitmap: &self.label_bitmap,
+            labels: self.labels.as_ref(),
+            node_id,
+            bitmap_index: node_start(&self.label_bitmap, node_id),
+        }
+    }
+
+    /// Returns all keys in sorted order.
+    #[allow(dead_code)]
+    pub fn keys(&self) -> Vec<Vec<u8>> {
+        let mut keys = vec![];
+        self.collect_keys(0, &mut vec![], &mut keys);
+        keys
+    }
+
+    /// Returns all keys within Levenshtein distance `max_distance` of `query`, along with the
+    /// distance, in sorted order.
+    ///
+    /// It walks the trie in depth first order with a row of the edit distance matrix for every
+    /// node, and skips a subtree once every value in the row exceeds `max_distance`.
+    #[allow(dead_code)]
+    pub fn fuzzy(&self, query: &[u8], max_distance: usize) -> Vec<(Vec<u8>, usize)> {
+        let mut res = vec![];
+
+        // The distances from the empty prefix to every prefix of `query`.
+        let row = (0..=query.len()).collect::<Vec<_>>();
+
+        self.fuzzy_walk(0, query, max_distance, &row, &mut vec![], &mut res);
+        res
+    }
+
+    /// Walk the subtree of `node_id`, where `row[j]` is the distance between `prefix` and
+    /// `query[..j]`.
+    fn fuzzy_walk(
+        &self,
+        node_id: i32,
+        query: &[u8],
+        max_distance: usize,
+        row: &[usize],
+        prefix: &mut Vec<u8>,
+        res: &mut Vec<(Vec<u8>, usize)>,
+    ) {
+        let dist = row[query.len()];
+        if dist <= max_distance && get_bit(self.leaves.as_ref(), node_id as usize) != 0 {
+            res.push((prefix.clone(), dist));
+        }
+
+        let mut next = vec![0; row.len()];
+        let mut buf = vec![0; row.len()];
+
+        // Every key below is at least as far as the min of a row.
+        let within = |r: &[usize]| r.iter().min().copied().unwrap() <= max_distance;
+
+        for (label, child) in self.children(node_id) {
+            let tail = self.tail(child);
+
+            edit_row(row, query, label, &mut next);
+            for b in tail {
+                if !within(&next) {
+                    break;
                 }
+                edit_row(&next, query, *b, &mut buf);
+                std::mem::swap(&mut next, &mut buf);
+            }
+
+            if within(&next) {
+                prefix.push(label);
+                prefix.extend_from_slice(tail);
+                self.fuzzy_walk(child, query, max_distance, &next, prefix, res);
+                prefix.truncate(prefix.len() - tail.len() - 1);
+            }
+        }
+    }
 
-                if self.labels[(bitmap_index - node_id) as usize] == *c {
-                    // matched
+    /// Returns all keys accepted by automaton `a`, in sorted order.
+    ///
+    /// The trie is walked along with `a`, and a subtree is skipped once the state can not match.
+    #[allow(dead_code)]
+    pub fn search<A: Automaton>(&self, a: &A) -> Vec<Vec<u8>> {
+        let mut res = vec![];
+        let state = a.start();
+
+        if a.can_match(&state) {
+            self.search_walk(0, a, &state, &mut vec![], &mut res);
+        }
+        res
+    }
+
+    fn search_walk<A: Automaton>(
+        &self,
+        node_id: i32,
+        a: &A,
+        state: &A::State,
+        prefix: &mut Vec<u8>,
+        res: &mut Vec<Vec<u8>>,
+    ) {
+        if a.is_match(state) && get_bit(self.leaves.as_ref(), node_id as usize) != 0 {
+            res.push(prefix.clone());
+        }
+
+        for (label, child) in self.children(node_id) {
+            let tail = self.tail(child);
+
+            let mut next = a.transition(state, label);
+            for b in tail {
+                if !a.can_match(&next) {
                     break;
                 }
+                next = a.transition(&next, *b);
+            }
 
-                bitmap_index += 1;
+            if a.can_match(&next) {
+                prefix.push(label);
+                prefix.extend_from_slice(tail);
+                self.search_walk(child, a, &next, prefix, res);
+                prefix.truncate(prefix.len() - tail.len() - 1);
             }
+        }
+    }
 
-            // go to next level
-            node_id = self.label_bitmap.count_zeros(bitmap_index + 1).0;
-            bitmap_index = self.label_bitmap.select_ith_one(node_id - 1) + 1;
+    /// Walk the subtree of `node_id` in depth first order and collect keys into `keys`.
+    /// `prefix` is the path from the root to `node_id`.
+    pub fn collect_keys(&self, node_id: i32, prefix: &mut Vec<u8>, keys: &mut Vec<Vec<u8>>) {
+        if get_bit(self.leaves.as_ref(), node_id as usize) != 0 {
+            keys.push(prefix.clone());
         }
 
-        get_bit(&self.leaves, node_id as usize) != 0
+        for (label, child) in self.children(node_id) {
+            let tail = self.tail(child);
+
+            prefix.push(label);
+            prefix.extend_from_slice(tail);
+            self.collect_keys(child, prefix, keys);
+            prefix.truncate(prefix.len() - tail.len() - 1);
+        }
     }
 }
 
-fn set_bit(words: &mut Vec<u64>, i: usize, value: u64) {
+/// Returns the index in `label_bitmap` of the first label of node `node_id`.
+pub fn node_start<LBM>(label_bitmap: &LBM, node_id: i32) -> i32
+where LBM: BitmapOps {
+    if node_id == 0 {
+        0
+    } else {
+        label_bitmap.select_ith_one(node_id - 1) + 1
+    }
+}
+
+/// Returns the range `[start, end)` in `label_bitmap` of the labels of node `node_id`.
+///
+/// The end of a node is its `1`, thus a single select of the `1`s around the node finds both ends.
+pub fn node_range<LBM>(label_bitmap: &LBM, node_id: i32) -> (i32, i32)
+where LBM: BitmapOps {
+    if node_id == 0 {
+        (0, label_bitmap.select_ith_one(0))
+    } else {
+        let (prev_end, end) = label_bitmap.select_2(node_id - 1);
+        (prev_end + 1, end)
+    }
+}
+
+/// Returns the bitmap index of label `c` of node `node_id`, whose labels are at `[start, end)` in
+/// the label bitmap, or `None` if there is no such label.
+///
+/// The labels are searched with `find_byte()` whatever the fanout. Binary search over the sorted
+/// labels is slower at every fanout up to 256: each step waits for the previous compare, while
+/// `find_byte()` compares 8 labels per step without such dependency. `bench_find_label` in
+/// `static_kv_test` measures both, on x86-64 per lookup:
+///
+/// | labels | `find_byte()` | binary search |
+/// | -:     | -:            | -:            |
+/// | 4      | 2.7 ns        | 15.7 ns       |
+/// | 16     | 5.5 ns        | 30.2 ns       |
+/// | 64     | 13.6 ns       | 57.5 ns       |
+/// | 256    | 26.9 ns       | 64.2 ns       |
+pub fn find_label(labels: &[u8], node_id: i32, start: i32, end: i32, c: u8) -> Option<i32> {
+    let node_labels = &labels[(start - node_id) as usize..(end - node_id) as usize];
+
+    find_byte(node_labels, c).map(|p| start + p as i32)
+}
+
+/// Returns the position of the first `c` in `bytes`, comparing 8 bytes at a time.
+pub fn find_byte(bytes: &[u8], c: u8) -> Option<usize> {
+    const LO: u64 = 0x0101010101010101;
+    const HI: u64 = 0x8080808080808080;
+
+    let pattern = LO * c as u64;
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut offset = 0;
+
+    for chunk in &mut chunks {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(chunk);
+
+        // A byte equal to `c` becomes 0, and `x - LO & !x & HI` marks the first zero byte.
+        let x = u64::from_le_bytes(buf) ^ pattern;
+        let zeros = x.wrapping_sub(LO) & !x & HI;
+
+        if zeros != 0 {
+            return Some(offset + (zeros.trailing_zeros() >> 3) as usize);
+        }
+        offset += 8;
+    }
+
+    chunks
+        .remainder()
+        .iter()
+        .position(|b| *b == c)
+        .map(|p| offset + p)
+}
+
+/// An iterator of the labels of a node and the children they lead to, in label order.
+/// If the trie has tails, an edge continues with the tail of the child after the label.
+pub struct Children<'a, LBM>
+where LBM: BitmapOps
+{
+    label_bitmap: &'a LBM,
+    labels: &'a [u8],
+    node_id: i32,
+    bitmap_index: i32,
+}
+
+impl<'a, LBM> Iterator for Children<'a, LBM>
+where LBM: BitmapOps
+{
+    type Item = (u8, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.label_bitmap.get_bit(self.bitmap_index) != 0 {
+            return None;
+        }
+
+        let label = self.labels[(self.bitmap_index - self.node_id) as usize];
+        let child = self.label_bitmap.count_zeros(self.bitmap_index + 1).0;
+
+        self.bitmap_index += 1;
+        Some((label, child))
+    }
+}
+
+/// Fill `next` with the row of the edit distance matrix after `row`, by appending `c` to the
+/// prefix: `next[j]` is the distance between the prefix and `query[..j]`.
+fn edit_row(row: &[usize], query: &[u8], c: u8, next: &mut [usize]) {
+    next[0] = row[0] + 1;
+    for j in 1..row.len() {
+        let replace = row[j - 1] + (query[j - 1] != c) as usize;
+        next[j] = replace.min(row[j] + 1).min(next[j - 1] + 1);
+    }
+}
+
+/// Returns the length of the longest common prefix of `a` and `b`.
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count()
+}
+
+pub fn set_bit(words: &mut Vec<u64>, i: usize, value: u64) {
     while i >> 6 >= words.len() {
         words.push(0);
     }
@@ -137,7 +663,6 @@ fn set_bit(words: &mut Vec<u64>, i: usize, value: u64) {
     words[i >> 6] |= value << (i & 63);
 }
 
-#[allow(dead_code)]
-fn get_bit(words: &[u64], i: usize) -> u64 {
+pub fn get_bit(words: &[u64], i: usize) -> u64 {
     words[i >> 6] & (1 << (i & 63))
 }
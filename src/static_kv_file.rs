@@ -0,0 +1,383 @@
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+use crate::bitmap::index::RankIndex;
+use crate::bitmap::index::SelectRankIndex;
+use crate::bitmap::indexed_bitmap::validate_index;
+use crate::bitmap::indexed_bitmap::IndexedBitmap;
+use crate::bitmap::rank::RankIndex64;
+use crate::bitmap::select::SelectIndex32;
+use crate::codec::crc32;
+use crate::codec::write_i32_slice;
+use crate::codec::write_u32;
+use crate::codec::write_u64;
+use crate::codec::write_u64_slice;
+use crate::codec::FormatError;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::StaticKV;
+use crate::static_kv::CTX;
+
+/// Magic number at the start of a `StaticKV` file.
+pub const KV_MAGIC: [u8; 8] = *b"SUCCINKV";
+
+/// Current version of the `StaticKV` file format.
+pub const KV_VERSION: u32 = 1;
+
+/// Size in bytes of the file header.
+pub const HEADER_SIZE: usize = 32;
+
+/// Size in bytes of an entry in the section table.
+pub const SECTION_ENTRY_SIZE: usize = 24;
+
+/// Section ids, in the order they are stored in a file.
+pub const SECTION_LEAVES: u32 = 1;
+pub const SECTION_LABEL_WORDS: u32 = 2;
+pub const SECTION_RANK_INDEX: u32 = 3;
+pub const SECTION_SELECT_INDEX: u32 = 4;
+pub const SECTION_LABELS: u32 = 5;
+
+const SECTIONS: [(u32, &str); 5] = [
+    (SECTION_LEAVES, "leaves"),
+    (SECTION_LABEL_WORDS, "label_bitmap"),
+    (SECTION_RANK_INDEX, "rank_index"),
+    (SECTION_SELECT_INDEX, "select_index"),
+    (SECTION_LABELS, "labels"),
+];
+
+/// Raw content of every section of a `StaticKV` file, borrowed from the file data.
+///
+/// The bounds and alignment of every section are verified, but the content is neither read nor
+/// decoded: see `verify_checksums()`.
+pub struct Sections<'a> {
+    pub leaves: &'a [u8],
+    pub label_words: &'a [u8],
+    pub rank_index: &'a [u8],
+    pub select_index: &'a [u8],
+    pub labels: &'a [u8],
+
+    /// The checksums in the section table, in the order of `SECTIONS`.
+    pub checksums: [u32; 5],
+}
+
+impl<'a> Sections<'a> {
+    /// Verify the checksum of every section. It reads all of the section data.
+    pub fn verify_checksums(&self) -> Result<(), FormatError> {
+        let sections = [
+            self.leaves,
+            self.label_words,
+            self.rank_index,
+            self.select_index,
+            self.labels,
+        ];
+
+        for (((_, name), section), checksum) in SECTIONS.iter().zip(sections).zip(self.checksums) {
+            if crc32(section) != checksum {
+                return Err(FormatError::ChecksumMismatch(name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The file format of a `StaticKV`.
+///
+/// All integers are little-endian. A file consists of a header, a section table and the sections.
+///
+/// Header, 32 bytes:
+///
+/// | offset | field      | type                  |
+/// | :--    | :--        | :--                   |
+/// | 0      | magic      | `[u8; 8]`: "SUCCINKV" |
+/// | 8      | version    | `u32`                 |
+/// | 12     | sections   | `u32`: count          |
+/// | 16     | file size  | `u64`                 |
+/// | 24     | checksum   | `u32`                 |
+/// | 28     | reserved   | `u32`: 0              |
+///
+/// The header checksum is a CRC-32 of the first 24 bytes of the header followed by the section
+/// table.
+///
+/// Section table, 24 bytes per section:
+///
+/// | offset | field      | type                              |
+/// | :--    | :--        | :--                               |
+/// | 0      | id         | `u32`                             |
+/// | 4      | checksum   | `u32`: CRC-32 of the section      |
+/// | 8      | offset     | `u64`: from the start of the file |
+/// | 16     | length     | `u64`: in bytes                   |
+///
+/// Every section starts at an 8-byte aligned offset and the gap before it is filled with 0.
+/// The sections are:
+///
+/// | id | content                             | type    |
+/// | :- | :--                                 | :--     |
+/// | 1  | `leaves`                            | `[u64]` |
+/// | 2  | words of `label_bitmap`             | `[u64]` |
+/// | 3  | rank index of `label_bitmap`        | `[i32]` |
+/// | 4  | select index of `label_bitmap`      | `[i32]` |
+/// | 5  | `labels`                            | `[u8]`  |
+impl StaticKV<LabelBitmap> {
+    /// Write the trie in the `StaticKV` file format.
+    ///
+    /// The format has no tails or original spellings, thus a trie built with `BuildOptions::tails`
+    /// or `BuildOptions::normalize` is rejected with an `InvalidInput` error.
+    #[allow(dead_code)]
+    pub fn save<W: Write>(&self, mut w: W) -> io::Result<()> {
+        if self.tails.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "tails are not supported by the file format",
+            ));
+        }
+        if self.originals.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "normalized keys are not supported by the file format",
+            ));
+        }
+
+        let mut bufs: [Vec<u8>; 5] = Default::default();
+
+        write_u64_slice(&mut bufs[0], &self.leaves)?;
+        write_u64_slice(&mut bufs[1], &self.label_bitmap.words)?;
+        write_i32_slice(&mut bufs[2], self.label_bitmap.index.get_rank_index())?;
+        write_i32_slice(&mut bufs[3], self.label_bitmap.index.get_select_index())?;
+        bufs[4].extend_from_slice(&self.labels);
+
+        let mut table = Vec::with_capacity(SECTIONS.len() * SECTION_ENTRY_SIZE);
+        let mut offsets = Vec::with_capacity(SECTIONS.len());
+        let mut offset = align8(HEADER_SIZE + SECTIONS.len() * SECTION_ENTRY_SIZE);
+
+        for ((id, _), buf) in SECTIONS.iter().zip(bufs.iter()) {
+            write_u32(&mut table, *id)?;
+            write_u32(&mut table, crc32(buf))?;
+            write_u64(&mut table, offset as u64)?;
+            write_u64(&mut table, buf.len() as u64)?;
+
+            offsets.push(offset);
+            offset = align8(offset + buf.len());
+        }
+
+        let file_size = offsets[SECTIONS.len() - 1] + bufs[SECTIONS.len() - 1].len();
+
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(&KV_MAGIC);
+        write_u32(&mut header, KV_VERSION)?;
+        write_u32(&mut header, SECTIONS.len() as u32)?;
+        write_u64(&mut header, file_size as u64)?;
+
+        let mut checked = header.clone();
+        checked.extend_from_slice(&table);
+        write_u32(&mut header, crc32(&checked))?;
+        write_u32(&mut header, 0)?;
+
+        w.write_all(&header)?;
+        w.write_all(&table)?;
+
+        let mut pos = HEADER_SIZE + table.len();
+        for (buf, offset) in bufs.iter().zip(offsets) {
+            w.write_all(&[0; 8][..offset - pos])?;
+            w.write_all(buf)?;
+            pos = offset + buf.len();
+        }
+
+        w.flush()
+    }
+
+    /// Load a trie written by `save()`.
+    ///
+    /// The indexes of `label_bitmap` are loaded as is and are validated but never rebuilt.
+    /// A corrupted or truncated file is rejected with an error.
+    #[allow(dead_code)]
+    pub fn load<R: Read>(mut r: R) -> Result<Self, FormatError> {
+        let mut data = vec![];
+        r.read_to_end(&mut data)?;
+
+        let sections = parse_sections(&data)?;
+        sections.verify_checksums()?;
+
+        let leaves = decode_u64s(sections.leaves);
+        let words = decode_u64s(sections.label_words);
+        let rank_index = decode_i32s(sections.rank_index);
+        let select_index = decode_i32s(sections.select_index);
+        let labels = sections.labels.to_vec();
+
+        validate_kv(&leaves, &words, &rank_index, &select_index, &labels)?;
+
+        Ok(StaticKV {
+            leaves,
+            label_bitmap: IndexedBitmap {
+                words,
+                index: SelectIndex32 {
+                    index: select_index,
+                    rank_index: RankIndex64 { index: rank_index },
+                    ctx: &CTX,
+                },
+            },
+            labels,
+            tails: None,
+            originals: None,
+        })
+    }
+}
+
+/// Parse the header and the section table of a `StaticKV` file.
+///
+/// Only the header and the section table are read and checked against the header checksum. The
+/// cost does not depend on the size of the sections.
+pub fn parse_sections(data: &[u8]) -> Result<Sections<'_>, FormatError> {
+    if data.len() < HEADER_SIZE {
+        return Err(FormatError::Truncated);
+    }
+
+    if data[..8] != KV_MAGIC {
+        return Err(FormatError::BadMagic);
+    }
+
+    let version = le_u32(&data[8..]);
+    if version != KV_VERSION {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+
+    let n = le_u32(&data[12..]) as usize;
+    if n != SECTIONS.len() {
+        return Err(FormatError::Invalid("section count"));
+    }
+
+    if le_u32(&data[28..]) != 0 {
+        return Err(FormatError::Invalid("reserved field"));
+    }
+
+    let file_size = le_u64(&data[16..]);
+    let table_end = HEADER_SIZE + n * SECTION_ENTRY_SIZE;
+
+    if (data.len() as u64) < file_size || data.len() < table_end {
+        return Err(FormatError::Truncated);
+    }
+
+    let mut checked = data[..24].to_vec();
+    checked.extend_from_slice(&data[HEADER_SIZE..table_end]);
+    if crc32(&checked) != le_u32(&data[24..]) {
+        return Err(FormatError::ChecksumMismatch("header"));
+    }
+
+    let mut found: [&[u8]; 5] = [&[]; 5];
+    let mut checksums = [0; 5];
+
+    // Sections are stored in order, each one right after the zero padding following the previous.
+    let mut pos = table_end as u64;
+
+    for (i, (id, _)) in SECTIONS.iter().enumerate() {
+        let entry = &data[HEADER_SIZE + i * SECTION_ENTRY_SIZE..];
+
+        if le_u32(entry) != *id {
+            return Err(FormatError::Invalid("section id"));
+        }
+
+        let offset = le_u64(&entry[8..]);
+        let len = le_u64(&entry[16..]);
+
+        if offset != align8(pos as usize) as u64 {
+            return Err(FormatError::Invalid("section offset"));
+        }
+
+        let end = offset
+            .checked_add(len)
+            .ok_or(FormatError::Invalid("section length"))?;
+        if end > file_size {
+            return Err(FormatError::Invalid("section length"));
+        }
+
+        if data[pos as usize..offset as usize].iter().any(|b| *b != 0) {
+            return Err(FormatError::Invalid("section padding"));
+        }
+
+        found[i] = &data[offset as usize..end as usize];
+        checksums[i] = le_u32(&entry[4..]);
+        pos = end;
+    }
+
+    if pos != file_size || data.len() as u64 != file_size {
+        return Err(FormatError::Invalid("file size"));
+    }
+
+    let [leaves, label_words, rank_index, select_index, labels] = found;
+
+    if leaves.len() % 8 != 0 || label_words.len() % 8 != 0 {
+        return Err(FormatError::Invalid("u64 section length"));
+    }
+
+    if rank_index.len() % 4 != 0 || select_index.len() % 4 != 0 {
+        return Err(FormatError::Invalid("i32 section length"));
+    }
+
+    Ok(Sections {
+        leaves,
+        label_words,
+        rank_index,
+        select_index,
+        labels,
+        checksums,
+    })
+}
+
+/// Check that the decoded parts of a `StaticKV` are consistent with each other, so that a lookup
+/// never reads out of bounds.
+pub fn validate_kv(
+    leaves: &[u64],
+    words: &[u64],
+    rank_index: &[i32],
+    select_index: &[i32],
+    labels: &[u8],
+) -> Result<(), FormatError> {
+    validate_index(words, rank_index, select_index)?;
+
+    // Every node is terminated by a `1` in label_bitmap.
+    let nodes = rank_index[words.len()];
+    if nodes == 0 {
+        return Err(FormatError::Invalid("empty label bitmap"));
+    }
+
+    if ((leaves.len() as i64) << 6) < nodes as i64 {
+        return Err(FormatError::Invalid("leaves size"));
+    }
+
+    // The bitmap ends with the `1` of the last node, and every `0` before it is a label.
+    let last_word = words.iter().rposition(|w| *w != 0).unwrap();
+    let bits_count = (last_word << 6) as i32 + 64 - words[last_word].leading_zeros() as i32;
+
+    if labels.len() as i32 != bits_count - nodes {
+        return Err(FormatError::Invalid("labels size"));
+    }
+
+    // Every node except the root has exactly one label pointing to it.
+    if labels.len() as i32 != nodes - 1 {
+        return Err(FormatError::Invalid("labels count"));
+    }
+
+    Ok(())
+}
+
+pub fn le_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+pub fn le_u64(b: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&b[..8]);
+    u64::from_le_bytes(buf)
+}
+
+fn decode_u64s(b: &[u8]) -> Vec<u64> {
+    b.chunks_exact(8).map(le_u64).collect()
+}
+
+fn decode_i32s(b: &[u8]) -> Vec<i32> {
+    b.chunks_exact(4).map(|x| le_u32(x) as i32).collect()
+}
+
+fn align8(n: usize) -> usize {
+    (n + 7) & !7
+}
@@ -0,0 +1,101 @@
+use crate::bitmap::index::RankIndex;
+use crate::bitmap::index::SelectRankIndex;
+use crate::codec::FormatError;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::StaticKV;
+use crate::static_kv_file::parse_sections;
+use crate::testing::keys;
+
+#[test]
+fn test_static_kv_save_load() -> anyhow::Result<()> {
+    let keys = keys();
+    let s = StaticKV::<LabelBitmap>::new(&keys);
+
+    let mut buf = vec![];
+    s.save(&mut buf)?;
+
+    assert_eq!(b"SUCCINKV", &buf[..8]);
+
+    let sections = parse_sections(&buf)?;
+    assert_eq!(s.leaves.len() * 8, sections.leaves.len());
+    assert_eq!(s.labels, sections.labels);
+
+    let got = StaticKV::<LabelBitmap>::load(&buf[..])?;
+
+    assert_eq!(s.leaves, got.leaves);
+    assert_eq!(s.labels, got.labels);
+    assert_eq!(s.label_bitmap.words, got.label_bitmap.words);
+    assert_eq!(
+        s.label_bitmap.index.get_rank_index(),
+        got.label_bitmap.index.get_rank_index()
+    );
+    assert_eq!(
+        s.label_bitmap.index.get_select_index(),
+        got.label_bitmap.index.get_select_index()
+    );
+
+    for k in keys.iter() {
+        assert!(got.has(k));
+    }
+    assert!(!got.has(b"Aar"));
+    assert!(!got.has(b"Abc"));
+
+    Ok(())
+}
+
+#[test]
+fn test_static_kv_load_truncated() -> anyhow::Result<()> {
+    let s = StaticKV::<LabelBitmap>::new(&keys());
+
+    let mut buf = vec![];
+    s.save(&mut buf)?;
+
+    for l in 0..buf.len() {
+        let res = StaticKV::<LabelBitmap>::load(&buf[..l]);
+        assert!(matches!(res, Err(FormatError::Truncated)), "len: {}", l);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_static_kv_load_corrupted() -> anyhow::Result<()> {
+    let s = StaticKV::<LabelBitmap>::new(&keys());
+
+    let mut buf = vec![];
+    s.save(&mut buf)?;
+
+    let mut b = buf.clone();
+    b[0] = b'X';
+    assert!(matches!(
+        StaticKV::<LabelBitmap>::load(&b[..]),
+        Err(FormatError::BadMagic)
+    ));
+
+    let mut b = buf.clone();
+    b[8] = 9;
+    assert!(matches!(
+        StaticKV::<LabelBitmap>::load(&b[..]),
+        Err(FormatError::UnsupportedVersion(9))
+    ));
+
+    let mut b = buf.clone();
+    let l = b.len();
+    b[l - 1] ^= 1;
+    assert!(matches!(
+        StaticKV::<LabelBitmap>::load(&b[..]),
+        Err(FormatError::ChecksumMismatch("labels"))
+    ));
+
+    // Any single flipped bit is detected.
+    for i in 0..buf.len() {
+        for bit in 0..8 {
+            let mut b = buf.clone();
+            b[i] ^= 1 << bit;
+            let res = StaticKV::<LabelBitmap>::load(&b[..]);
+            assert!(res.is_err(), "flipped bit {} of byte {}", bit, i);
+        }
+    }
+
+    Ok(())
+}
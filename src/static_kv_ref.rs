@@ -0,0 +1,78 @@
+use crate::bitmap::indexed_bitmap::IndexedBitmap;
+use crate::bitmap::rank::RankIndex64;
+use crate::bitmap::select::SelectIndex32;
+use crate::codec::cast_i32_slice;
+use crate::codec::cast_u64_slice;
+use crate::codec::FormatError;
+use crate::static_kv::StaticKV;
+use crate::static_kv::CTX;
+use crate::static_kv_file::parse_sections;
+use crate::static_kv_file::validate_kv;
+
+/// A `LabelBitmap` that borrows its words and indexes.
+pub type LabelBitmapRef<'a> =
+    IndexedBitmap<SelectIndex32<RankIndex64<&'a [i32]>, &'a [i32]>, &'a [u64]>;
+
+/// A read-only `StaticKV` that borrows all of its data from a byte slice in the `StaticKV` file
+/// format, such as a memory-mapped file.
+///
+/// Nothing is copied and no index is rebuilt when it is created, and it supports every query of
+/// `StaticKV`.
+pub type StaticKVRef<'a> = StaticKV<LabelBitmapRef<'a>, &'a [u64], &'a [u8]>;
+
+impl<'a> StaticKVRef<'a> {
+    /// Create a view of the data written by `StaticKV::save()`, after a full `verify()` of it.
+    ///
+    /// `data` must be 8-byte aligned, which is always true for a memory-mapped file.
+    /// Borrowing in place is only supported on little-endian platforms.
+    #[allow(dead_code)]
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, FormatError> {
+        Self::verify(data)?;
+        Self::from_bytes_unchecked(data)
+    }
+
+    /// Create a view of the data written by `StaticKV::save()` without reading the sections.
+    ///
+    /// Only the header and the section table are checked, thus it takes the same time for any
+    /// size of data. The sections are trusted: if they are corrupted, a query may return a wrong
+    /// answer or panic, but never reads outside of `data`. Call `verify()` to check them when it
+    /// is convenient, e.g., in the background after the trie started serving.
+    #[allow(dead_code)]
+    pub fn from_bytes_unchecked(data: &'a [u8]) -> Result<Self, FormatError> {
+        let sections = parse_sections(data)?;
+
+        Ok(StaticKV {
+            leaves: cast_u64_slice(sections.leaves)?,
+            label_bitmap: IndexedBitmap {
+                words: cast_u64_slice(sections.label_words)?,
+                index: SelectIndex32 {
+                    index: cast_i32_slice(sections.select_index)?,
+                    rank_index: RankIndex64 {
+                        index: cast_i32_slice(sections.rank_index)?,
+                    },
+                    ctx: &CTX,
+                },
+            },
+            labels: sections.labels,
+            tails: None,
+            originals: None,
+        })
+    }
+
+    /// Verify the checksum of every section of `data` and the consistency of the indexes in it.
+    ///
+    /// It reads all of `data`, and the index check takes time linear to the size of the bitmap.
+    #[allow(dead_code)]
+    pub fn verify(data: &[u8]) -> Result<(), FormatError> {
+        let sections = parse_sections(data)?;
+        sections.verify_checksums()?;
+
+        validate_kv(
+            cast_u64_slice(sections.leaves)?,
+            cast_u64_slice(sections.label_words)?,
+            cast_i32_slice(sections.rank_index)?,
+            cast_i32_slice(sections.select_index)?,
+            sections.labels,
+        )
+    }
+}
@@ -0,0 +1,131 @@
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::codec::FormatError;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::StaticKV;
+use crate::static_kv_ref::StaticKVRef;
+use crate::testing::keys;
+
+/// Copy bytes into a 8-byte aligned place, such as a memory-mapped file.
+/// It returns the buffer and the offset of the copy in it.
+fn aligned(buf: &[u8]) -> (Vec<u8>, usize) {
+    let mut mem = vec![0u8; buf.len() + 8];
+    let offset = mem.as_ptr().align_offset(8);
+    assert!(offset < 8);
+
+    mem[offset..offset + buf.len()].copy_from_slice(buf);
+    (mem, offset)
+}
+
+#[test]
+fn test_static_kv_ref_has() -> anyhow::Result<()> {
+    let keys = keys();
+    let s = StaticKV::<LabelBitmap>::new(&keys);
+
+    let mut buf = vec![];
+    s.save(&mut buf)?;
+
+    let (mem, offset) = aligned(&buf);
+    let data = &mem[offset..offset + buf.len()];
+
+    let r = StaticKVRef::from_bytes(data)?;
+
+    // borrowed in place
+    assert_eq!(s.labels, r.labels);
+    assert_eq!(s.label_bitmap.words, r.label_bitmap.words);
+    assert!(data.as_ptr_range().contains(&r.labels.as_ptr()));
+    assert!(data
+        .as_ptr_range()
+        .contains(&(r.label_bitmap.words.as_ptr() as *const u8)));
+
+    for k in keys.iter() {
+        assert!(r.has(k), "key: {:?}", k);
+    }
+
+    for k in ["", "a", "Aa", "Aaro", "Aaronicalx", "Abab", "Ac", "B"] {
+        assert_eq!(s.has(k.as_bytes()), r.has(k.as_bytes()), "key: {}", k);
+    }
+
+    assert_eq!(keys, r.keys());
+
+    Ok(())
+}
+
+#[test]
+fn test_static_kv_ref_misaligned() -> anyhow::Result<()> {
+    let s = StaticKV::<LabelBitmap>::new(&keys());
+
+    let mut buf = vec![0];
+    s.save(&mut buf)?;
+
+    let (mem, offset) = aligned(&buf);
+    let data = &mem[offset + 1..offset + buf.len()];
+
+    let res = StaticKVRef::from_bytes(data);
+    assert!(matches!(res, Err(FormatError::Misaligned)));
+
+    let res = StaticKVRef::from_bytes_unchecked(data);
+    assert!(matches!(res, Err(FormatError::Misaligned)));
+
+    Ok(())
+}
+
+#[test]
+fn test_static_kv_ref_corrupted() -> anyhow::Result<()> {
+    let s = StaticKV::<LabelBitmap>::new(&keys());
+
+    let mut buf = vec![];
+    s.save(&mut buf)?;
+
+    let l = buf.len();
+    buf[l - 1] ^= 1;
+
+    let (mem, offset) = aligned(&buf);
+    let data = &mem[offset..offset + l];
+
+    let res = StaticKVRef::from_bytes(data);
+    assert!(matches!(res, Err(FormatError::ChecksumMismatch("labels"))));
+
+    let res = StaticKVRef::verify(data);
+    assert!(matches!(res, Err(FormatError::ChecksumMismatch("labels"))));
+
+    // Sections are not read without verification.
+    let r = StaticKVRef::from_bytes_unchecked(data)?;
+    assert_eq!(s.labels.len(), r.labels.len());
+
+    let res = StaticKVRef::from_bytes(&data[..l - 1]);
+    assert!(matches!(res, Err(FormatError::Truncated)));
+
+    let res = StaticKVRef::from_bytes_unchecked(&data[..l - 1]);
+    assert!(matches!(res, Err(FormatError::Truncated)));
+
+    Ok(())
+}
+
+#[test]
+fn test_static_kv_ref_inconsistent() -> anyhow::Result<()> {
+    let s = StaticKV::<LabelBitmap>::new(&keys());
+
+    // A label bitmap that does not match the labels, with valid checksums.
+    let bad = StaticKV::<LabelBitmap> {
+        leaves: s.leaves,
+        label_bitmap: LabelBitmap::new(vec![0b1011]),
+        labels: s.labels,
+        tails: None,
+        originals: None,
+    };
+
+    let mut buf = vec![];
+    bad.save(&mut buf)?;
+
+    let (mem, offset) = aligned(&buf);
+    let data = &mem[offset..offset + buf.len()];
+
+    assert!(matches!(
+        StaticKVRef::verify(data),
+        Err(FormatError::Invalid(_))
+    ));
+    assert!(StaticKVRef::from_bytes(data).is_err());
+    assert!(StaticKVRef::from_bytes_unchecked(data).is_ok());
+
+    Ok(())
+}
@@ -1,10 +1,21 @@
+use std::time::Duration;
+use std::time::Instant;
+
 use pretty_assertions::assert_eq;
 
 use crate::bitmap::fmt::fmt_bitmap;
 use crate::bitmap::index::RankIndex;
 use crate::bitmap::index::SelectRankIndex;
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::static_kv::find_byte;
+use crate::static_kv::node_range;
+use crate::static_kv::BuildOptions;
+use crate::static_kv::ByteMap;
 use crate::static_kv::LabelBitmap;
 use crate::static_kv::StaticKV;
+use crate::testing;
+use crate::testing::lcg;
+use crate::testing::to_vecs;
 
 fn absent_keys() -> Vec<String> {
     let x = vec!["AB", "abx", "bC", "bcX", "xyz"];
@@ -140,6 +151,7 @@ fn test_kv() -> anyhow::Result<()> {
         };
 
         assert_eq!(c.want, got);
+        assert_eq!(to_vecs(&c.keys), s.keys());
 
         for k in c.keys.iter() {
             let k = k.as_bytes().to_vec();
@@ -157,11 +169,482 @@ fn test_kv() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn to_vecs(v: &[&'static str]) -> Vec<Vec<u8>> {
-    let mut res = vec![];
-    for s in v {
-        res.push(s.as_bytes().to_vec())
+#[test]
+fn test_kv_children() -> anyhow::Result<()> {
+    let keys = to_vecs(&["ab", "ac", "b", "bcd"]);
+    let s = StaticKV::<LabelBitmap>::new(&keys);
+
+    // Nodes: root, a, b, ab, ac, bc, bcd
+    assert_eq!(
+        vec![(b'a', 1), (b'b', 2)],
+        s.children(0).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![(b'b', 3), (b'c', 4)],
+        s.children(1).collect::<Vec<_>>()
+    );
+    assert_eq!(vec![(b'c', 5)], s.children(2).collect::<Vec<_>>());
+    assert_eq!(vec![(b'd', 6)], s.children(5).collect::<Vec<_>>());
+    assert_eq!(0, s.children(6).count());
+
+    assert_eq!(Some(4), s.child(1, b'c'));
+    assert_eq!(None, s.child(1, b'd'));
+    assert_eq!(None, s.child(6, b'a'));
+
+    assert_eq!(Some(0), s.find_node(b""));
+    assert_eq!(Some(5), s.find_node(b"bc"));
+    assert_eq!(Some(6), s.find_node(b"bcd"));
+    assert_eq!(None, s.find_node(b"bd"));
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_keys_with_prefix() -> anyhow::Result<()> {
+    let keys = to_vecs(&["ab", "abc", "b", "bcd", "bce"]);
+    let s = StaticKV::<LabelBitmap>::new(&keys);
+
+    assert_eq!(keys, s.keys_with_prefix(b""));
+    assert_eq!(to_vecs(&["ab", "abc"]), s.keys_with_prefix(b"a"));
+    assert_eq!(to_vecs(&["abc"]), s.keys_with_prefix(b"abc"));
+    assert_eq!(to_vecs(&["bcd", "bce"]), s.keys_with_prefix(b"bc"));
+    assert_eq!(Vec::<Vec<u8>>::new(), s.keys_with_prefix(b"abcd"));
+    assert_eq!(Vec::<Vec<u8>>::new(), s.keys_with_prefix(b"c"));
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_tails() -> anyhow::Result<()> {
+    let opts = BuildOptions {
+        tails: true,
+        ..Default::default()
+    };
+
+    let keys = to_vecs(&["ab", "abcdef", "abcdxy", "b", "bcd", "xyz"]);
+    let s = StaticKV::<LabelBitmap>::with_options(&keys, &opts);
+
+    // 0 -ab-> 1 -cd-> 4 -ef-> 6
+    //                  -xy-> 7
+    //   -b-> 2 -cd-> 5
+    //   -xyz-> 3
+    assert_eq!("abxccex", String::from_utf8(s.labels.clone())?);
+    assert_eq!(b"b", s.tail(1));
+    assert_eq!(b"yz", s.tail(3));
+    assert_eq!(b"d", s.tail(4));
+    assert_eq!(b"f", s.tail(6));
+    assert_eq!(b"y", s.tail(7));
+    assert_eq!(b"d", s.tail(5));
+    assert_eq!(b"", s.tail(2));
+
+    assert_eq!(Some(4), s.find_node(b"abcd"));
+    assert_eq!(None, s.find_node(b"abc"));
+
+    assert_eq!(keys, s.keys());
+
+    for k in keys.iter() {
+        assert!(s.has(k), "key: {:?}", k);
+    }
+
+    for k in [
+        "", "a", "abc", "abcd", "abcde", "abcdefg", "bc", "bcde", "x", "xy", "xyzz",
+    ] {
+        assert!(!s.has(k.as_bytes()), "absent key: {}", k);
     }
 
-    res
+    assert_eq!(to_vecs(&["abcdef", "abcdxy"]), s.keys_with_prefix(b"abc"));
+    assert_eq!(to_vecs(&["abcdef", "abcdxy"]), s.keys_with_prefix(b"abcd"));
+    assert_eq!(to_vecs(&["abcdxy"]), s.keys_with_prefix(b"abcdx"));
+    assert_eq!(to_vecs(&["xyz"]), s.keys_with_prefix(b"xy"));
+    assert_eq!(Vec::<Vec<u8>>::new(), s.keys_with_prefix(b"abce"));
+    assert_eq!(Vec::<Vec<u8>>::new(), s.keys_with_prefix(b"xyzz"));
+
+    // The same answers as without tails.
+    let keys = testing::keys();
+    let plain = StaticKV::<LabelBitmap>::new(&keys);
+    let s = StaticKV::<RleBitmap>::with_options(&keys, &opts);
+
+    assert_eq!(keys, s.keys());
+
+    for k in keys.iter() {
+        for l in 0..=k.len() + 1 {
+            let mut p = k[..l.min(k.len())].to_vec();
+            if l > k.len() {
+                p.push(b'x');
+            }
+
+            assert_eq!(plain.has(&p), s.has(&p), "key: {:?}", p);
+            assert_eq!(
+                plain.keys_with_prefix(&p),
+                s.keys_with_prefix(&p),
+                "prefix: {:?}",
+                p
+            );
+        }
+    }
+
+    // A tail trie can not be saved.
+    let mut buf = vec![];
+    assert!(StaticKV::<LabelBitmap>::with_options(&keys, &opts)
+        .save(&mut buf)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_tails_urls() -> anyhow::Result<()> {
+    let mut rng = lcg(7);
+
+    let hosts = [
+        "example.com",
+        "docs.example.org",
+        "www.rust-lang.org",
+        "github.com",
+    ];
+
+    let mut keys = (0..2000)
+        .map(|_| {
+            let host = hosts[rng(hosts.len() as u64) as usize];
+            format!(
+                "https://{}/{:x}/items/{:x}?ref=index",
+                host,
+                rng(1 << 20),
+                rng(1 << 30)
+            )
+            .into_bytes()
+        })
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    let plain = StaticKV::<LabelBitmap>::new(&keys);
+    let s = StaticKV::<LabelBitmap>::with_options(&keys, &BuildOptions {
+        tails: true,
+        ..Default::default()
+    });
+
+    assert_eq!(keys, s.keys());
+    for k in keys.iter() {
+        assert!(s.has(k));
+    }
+
+    // Labels and bitmaps are bounded by the number of branches, not by the bytes of the keys.
+    let tails = s.tails.as_ref().unwrap();
+    let size = |kv: &StaticKV<LabelBitmap>| kv.labels.len() + kv.label_bitmap.words.len() * 8;
+    let tail_size = tails.bytes.len()
+        + tails.starts.words.len() * 8
+        + tails.edges.words.len() * 8
+        + tails.ends.words.len() * 8;
+
+    assert!(size(&s) * 10 < size(&plain));
+    assert!(
+        size(&s) + tail_size < size(&plain),
+        "tails: {} + {}, plain: {}",
+        size(&s),
+        tail_size,
+        size(&plain)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_high_fanout() -> anyhow::Result<()> {
+    // A root with 250 children, each with a few children.
+    let mut keys = vec![];
+    for a in 3..253u8 {
+        keys.push(vec![a]);
+        for b in (0..=255u8).step_by(a as usize) {
+            keys.push(vec![a, b]);
+        }
+    }
+
+    let s = StaticKV::<LabelBitmap>::new(&keys);
+    let rle = StaticKV::<RleBitmap>::new(&keys);
+
+    for a in 0..=255u8 {
+        for b in 0..=255u8 {
+            let want = (3..253).contains(&a) && b % a == 0;
+            assert_eq!(want, s.has(&[a, b]), "key: {:?}", [a, b]);
+            assert_eq!(want, rle.has(&[a, b]), "key: {:?}", [a, b]);
+        }
+        assert_eq!((3..253).contains(&a), s.has(&[a]));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_node_range() -> anyhow::Result<()> {
+    let keys = to_vecs(&["ab", "ac", "b", "bcd"]);
+    let s = StaticKV::<LabelBitmap>::new(&keys);
+    let rle = StaticKV::<RleBitmap>::new(&keys);
+
+    // 0010010110111
+    let want = [(0, 2), (3, 5), (6, 7), (8, 8), (9, 9), (10, 11), (12, 12)];
+
+    for (node_id, want) in want.iter().enumerate() {
+        assert_eq!(*want, node_range(&s.label_bitmap, node_id as i32));
+        assert_eq!(*want, node_range(&rle.label_bitmap, node_id as i32));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_fuzzy() -> anyhow::Result<()> {
+    let keys = to_vecs(&[
+        "", "apple", "apply", "banana", "band", "bandana", "can", "cant",
+    ]);
+    let s = StaticKV::<LabelBitmap>::new(&keys);
+    let t = StaticKV::<LabelBitmap>::with_options(&keys, &BuildOptions {
+        tails: true,
+        ..Default::default()
+    });
+
+    let got = s.fuzzy(b"appel", 2);
+    assert_eq!(vec![(b"apple".to_vec(), 2), (b"apply".to_vec(), 2)], got);
+
+    let got = s.fuzzy(b"can", 1);
+    assert_eq!(vec![(b"can".to_vec(), 0), (b"cant".to_vec(), 1)], got);
+
+    let got = s.fuzzy(b"", 0);
+    assert_eq!(vec![(b"".to_vec(), 0)], got);
+
+    assert_eq!(0, s.fuzzy(b"xyz", 2).len());
+
+    // Compare with a naive edit distance.
+    let dist = |a: &[u8], b: &[u8]| -> usize {
+        let mut row = (0..=b.len()).collect::<Vec<_>>();
+        for (i, x) in a.iter().enumerate() {
+            let mut prev = row[0];
+            row[0] = i + 1;
+            for j in 1..=b.len() {
+                let cur = row[j];
+                row[j] = (prev + (*x != b[j - 1]) as usize)
+                    .min(row[j] + 1)
+                    .min(row[j - 1] + 1);
+                prev = cur;
+            }
+        }
+        row[b.len()]
+    };
+
+    for q in ["", "ban", "bnaana", "cnat", "aple", "bandanas", "zz"] {
+        for max in 0..4 {
+            let want = keys
+                .iter()
+                .map(|k| (k.clone(), dist(k, q.as_bytes())))
+                .filter(|(_, d)| *d <= max)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                want,
+                s.fuzzy(q.as_bytes(), max),
+                "query: {}, max: {}",
+                q,
+                max
+            );
+            assert_eq!(want, t.fuzzy(q.as_bytes(), max), "tails, query: {}", q);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_normalize() -> anyhow::Result<()> {
+    let opts = BuildOptions {
+        normalize: Some(ByteMap::ascii_lowercase()),
+        ..Default::default()
+    };
+
+    // Not sorted, and "Email" and "EMAIL" are the same key.
+    let keys = to_vecs(&["UserName", "userId", "Email", "EMAIL", "user"]);
+    let s = StaticKV::<LabelBitmap>::with_options(&keys, &opts);
+
+    assert_eq!(to_vecs(&["email", "user", "userid", "username"]), s.keys());
+
+    for k in [
+        "username", "USERNAME", "UserName", "userid", "email", "User",
+    ] {
+        assert!(s.has(k.as_bytes()), "key: {}", k);
+    }
+    assert!(!s.has(b"users"));
+    assert!(!s.has(b"use"));
+    assert_eq!(s.find_node(b"user"), s.find_node(b"USER"));
+
+    assert_eq!(Some(&b"UserName"[..]), s.original(b"USERNAME"));
+    assert_eq!(Some(&b"userId"[..]), s.original(b"USERID"));
+    // The first spelling is kept.
+    assert_eq!(Some(&b"Email"[..]), s.original(b"email"));
+    assert_eq!(None, s.original(b"use"));
+
+    assert_eq!(
+        to_vecs(&["user", "userid", "username"]),
+        s.keys_with_prefix(b"USER")
+    );
+    assert_eq!(
+        vec![&b"user"[..], &b"userId"[..], &b"UserName"[..]],
+        s.originals_with_prefix(b"USER")
+    );
+    assert_eq!(4, s.originals_with_prefix(b"").len());
+    assert_eq!(0, s.originals_with_prefix(b"x").len());
+
+    // A trie without normalization has no original spelling.
+    let plain = StaticKV::<LabelBitmap>::new(&to_vecs(&["a", "b"]));
+    assert_eq!(None, plain.original(b"a"));
+    assert_eq!(0, plain.originals_with_prefix(b"").len());
+
+    // Original spellings can not be saved.
+    let mut buf = vec![];
+    assert!(s.save(&mut buf).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_normalize_custom_map() -> anyhow::Result<()> {
+    // Treat '-' and '_' as the same.
+    let opts = BuildOptions {
+        normalize: Some(ByteMap::new(|b| if b == b'-' { b'_' } else { b })),
+        ..Default::default()
+    };
+
+    let keys = to_vecs(&["foo-bar", "foo_baz", "qux"]);
+    let s = StaticKV::<LabelBitmap>::with_options(&keys, &opts);
+
+    assert!(s.has(b"foo_bar"));
+    assert!(s.has(b"foo-baz"));
+    assert!(!s.has(b"FOO_BAR"));
+    assert_eq!(Some(&b"foo-bar"[..]), s.original(b"foo_bar"));
+    assert_eq!(
+        vec![&b"foo-bar"[..], &b"foo_baz"[..]],
+        s.originals_with_prefix(b"foo-")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_kv_normalize_random() -> anyhow::Result<()> {
+    let mut rand = lcg(29);
+
+    let keys = (0..500)
+        .map(|_| {
+            let len = rand(6);
+            (0..len)
+                .map(|_| b"aAbBc"[rand(5) as usize])
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // The first spelling of every normalized key.
+    let mut want = std::collections::BTreeMap::new();
+    for k in keys.iter() {
+        want.entry(k.to_ascii_lowercase())
+            .or_insert_with(|| k.clone());
+    }
+
+    for tails in [false, true] {
+        let opts = BuildOptions {
+            tails,
+            normalize: Some(ByteMap::ascii_lowercase()),
+        };
+        let s = StaticKV::<LabelBitmap>::with_options(&keys, &opts);
+
+        assert_eq!(want.keys().cloned().collect::<Vec<_>>(), s.keys());
+
+        for (norm, original) in want.iter() {
+            assert_eq!(
+                Some(original.as_slice()),
+                s.original(norm),
+                "key: {:?}",
+                norm
+            );
+        }
+
+        assert_eq!(
+            want.iter()
+                .filter(|(k, _)| k.starts_with(b"ab"))
+                .map(|(_, v)| v.as_slice())
+                .collect::<Vec<_>>(),
+            s.originals_with_prefix(b"Ab")
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_find_byte() -> anyhow::Result<()> {
+    let bytes = (0..40u8).map(|x| x * 3).collect::<Vec<_>>();
+
+    for len in 0..bytes.len() {
+        let bs = &bytes[..len];
+        for c in 0..=255u8 {
+            assert_eq!(
+                bs.iter().position(|b| *b == c),
+                find_byte(bs, c),
+                "len: {}, c: {}",
+                len,
+                c
+            );
+        }
+    }
+
+    assert_eq!(Some(1), find_byte(&[1, 0x80, 0x80], 0x80));
+    assert_eq!(Some(2), find_byte(&[0x7f, 0xff, 0, 0, 0, 0, 0, 0, 0], 0));
+
+    Ok(())
+}
+
+/// Compares `find_byte()` and binary search over sorted labels of several sizes, for
+/// `find_label()`. Run it with:
+///
+/// ```text
+/// cargo test --release bench_find_label -- --ignored --nocapture
+/// ```
+#[test]
+#[ignore]
+fn bench_find_label() -> anyhow::Result<()> {
+    let mut rng = lcg(17);
+
+    for n in [4, 8, 12, 16, 24, 32, 48, 64, 128, 256] {
+        let mut labels = (0..n).map(|i| (i * 256 / n) as u8).collect::<Vec<_>>();
+        labels.dedup();
+
+        let queries = (0..1 << 16).map(|_| rng(256) as u8).collect::<Vec<_>>();
+
+        let rounds = 100;
+        let mut found = 0;
+
+        let t = Instant::now();
+        for _ in 0..rounds {
+            for c in queries.iter() {
+                found += find_byte(&labels, *c).unwrap_or(0);
+            }
+        }
+        let linear = t.elapsed();
+
+        let t = Instant::now();
+        for _ in 0..rounds {
+            for c in queries.iter() {
+                found += labels.binary_search(c).unwrap_or(0);
+            }
+        }
+        let binary = t.elapsed();
+
+        let per = |d: Duration| d.as_nanos() as f64 / (rounds * queries.len()) as f64;
+        println!(
+            "labels: {:>3}, find_byte: {:.2} ns, binary_search: {:.2} ns ({})",
+            n,
+            per(linear),
+            per(binary),
+            found
+        );
+    }
+
+    Ok(())
 }
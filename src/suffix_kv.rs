@@ -0,0 +1,152 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::int_vec::IntVec;
+use crate::static_kv::get_bit;
+use crate::static_kv::node_range;
+use crate::static_kv::StaticKV;
+
+/// A `StaticKV` with a companion trie of the reversed keys, for suffix queries.
+///
+/// A key id is the index of the key in sorted order, in either trie. The keys in the subtree of a
+/// node of `reversed` are all keys ending with the reversed path to it, and their reversed ids are
+/// a continuous range:
+/// - `first_key[v]` is the reversed id of the first key in the subtree of `v`;
+/// - `key_count[v]` is the number of keys in the subtree of `v`.
+///
+/// `to_forward[i]` is the forward id of the key with reversed id `i`.
+pub struct SuffixKV<LBM>
+where LBM: BitmapOps
+{
+    pub forward: StaticKV<LBM>,
+    pub reversed: StaticKV<LBM>,
+    pub first_key: IntVec,
+    pub key_count: IntVec,
+    pub to_forward: IntVec,
+}
+
+impl<LBM> SuffixKV<LBM>
+where LBM: BuildBitmap
+{
+    /// Build from a list of keys, in any order.
+    #[allow(dead_code)]
+    pub fn new(keys: &[Vec<u8>]) -> Self {
+        let mut keys = keys.to_vec();
+        keys.sort();
+        keys.dedup();
+
+        let mut rev = keys
+            .iter()
+            .enumerate()
+            .map(|(id, k)| (k.iter().rev().copied().collect::<Vec<_>>(), id as u64))
+            .collect::<Vec<_>>();
+        rev.sort();
+
+        let rev_keys = rev.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+        let to_forward = rev.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+
+        let forward = StaticKV::<LBM>::new(&keys);
+        let reversed = StaticKV::<LBM>::new(&rev_keys);
+
+        let nodes = reversed.labels.len() + 1;
+        let bm = &reversed.label_bitmap;
+        let is_key = |v: usize| get_bit(&reversed.leaves, v) != 0;
+
+        // Sum up the counts bottom-up, in reverse breadth first order.
+        let mut key_count = (0..nodes).map(|v| is_key(v) as u64).collect::<Vec<_>>();
+        for v in (1..nodes).rev() {
+            let parent = bm.count_ones(bm.select_ith_zero(v as i32 - 1)).0 as usize;
+            key_count[parent] += key_count[v];
+        }
+
+        // The children of a node are consecutive node ids, in label order.
+        let mut first_key = vec![0u64; nodes];
+        let mut child = 1;
+        for v in 0..nodes {
+            let mut next = first_key[v] + is_key(v) as u64;
+
+            let (start, end) = node_range(bm, v as i32);
+            for _ in start..end {
+                first_key[child] = next;
+                next += key_count[child];
+                child += 1;
+            }
+        }
+
+        SuffixKV {
+            forward,
+            reversed,
+            first_key: IntVec::from_values(&first_key),
+            key_count: IntVec::from_values(&key_count),
+            to_forward: IntVec::from_values(&to_forward),
+        }
+    }
+}
+
+impl<LBM> SuffixKV<LBM>
+where LBM: BitmapOps
+{
+    #[allow(dead_code)]
+    pub fn has(&self, key: &[u8]) -> bool {
+        self.forward.has(key)
+    }
+
+    /// Returns the forward id of `key`, or `None` if it is not a key.
+    #[allow(dead_code)]
+    pub fn key_id(&self, key: &[u8]) -> Option<i32> {
+        let v = self.reversed_node(key)?;
+
+        if get_bit(&self.reversed.leaves, v) == 0 {
+            return None;
+        }
+
+        // The key itself is the first in its subtree.
+        let rev_id = self.first_key.get(v);
+        Some(self.to_forward.get(rev_id as usize) as i32)
+    }
+
+    /// Returns the number of keys ending with `suffix`.
+    #[allow(dead_code)]
+    pub fn count_ends_with(&self, suffix: &[u8]) -> i32 {
+        match self.reversed_node(suffix) {
+            None => 0,
+            Some(v) => self.key_count.get(v) as i32,
+        }
+    }
+
+    /// Returns all keys ending with `suffix` with their forward ids, ordered by id.
+    ///
+    /// The keys are collected in reversed order and then sorted by id, which takes
+    /// `O(m log m)` for `m` keys. Use `count_ends_with()` if only the number is needed.
+    #[allow(dead_code)]
+    pub fn ends_with(&self, suffix: &[u8]) -> Vec<(i32, Vec<u8>)> {
+        let v = match self.reversed_node(suffix) {
+            None => return vec![],
+            Some(v) => v,
+        };
+
+        let mut path = suffix.iter().rev().copied().collect::<Vec<_>>();
+        let mut rev_keys = vec![];
+        self.reversed
+            .collect_keys(v as i32, &mut path, &mut rev_keys);
+
+        let first = self.first_key.get(v) as usize;
+
+        let mut res = rev_keys
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut k)| {
+                k.reverse();
+                (self.to_forward.get(first + i) as i32, k)
+            })
+            .collect::<Vec<_>>();
+
+        res.sort_unstable();
+        res
+    }
+
+    /// Returns the node in the reversed trie at the end of the reversed path `key`.
+    fn reversed_node(&self, key: &[u8]) -> Option<usize> {
+        let rev = key.iter().rev().copied().collect::<Vec<_>>();
+        self.reversed.find_node(&rev).map(|v| v as usize)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::static_kv::LabelBitmap;
+use crate::suffix_kv::SuffixKV;
+use crate::testing::lcg;
+use crate::testing::to_vecs;
+
+#[test]
+fn test_suffix_kv_example() -> anyhow::Result<()> {
+    let keys = to_vecs(&[
+        "api.example.com",
+        "example.com",
+        "example.org",
+        "mail.example.com",
+        "notexample.com",
+    ]);
+    let s = SuffixKV::<LabelBitmap>::new(&keys);
+
+    assert_eq!(2, s.count_ends_with(b".example.com"));
+    assert_eq!(
+        vec![(0, keys[0].clone()), (3, keys[3].clone())],
+        s.ends_with(b".example.com")
+    );
+    assert_eq!(4, s.count_ends_with(b"example.com"));
+    assert_eq!(5, s.count_ends_with(b""));
+    assert_eq!(0, s.count_ends_with(b".net"));
+    assert_eq!(Vec::<(i32, Vec<u8>)>::new(), s.ends_with(b".net"));
+
+    for (i, k) in keys.iter().enumerate() {
+        assert!(s.has(k));
+        assert_eq!(Some(i as i32), s.key_id(k));
+    }
+    assert_eq!(None, s.key_id(b"example"));
+    assert_eq!(None, s.key_id(b"com"));
+
+    // Unsorted input with duplicates gets the same ids.
+    let mut shuffled = keys.clone();
+    shuffled.reverse();
+    shuffled.push(keys[1].clone());
+
+    let s = SuffixKV::<LabelBitmap>::new(&shuffled);
+    for (i, k) in keys.iter().enumerate() {
+        assert_eq!(Some(i as i32), s.key_id(k));
+    }
+    assert_eq!(
+        vec![(0, keys[0].clone()), (3, keys[3].clone())],
+        s.ends_with(b".example.com")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_suffix_kv() -> anyhow::Result<()> {
+    let mut rand = lcg(23);
+
+    let mut keys = (0..1000)
+        .map(|_| {
+            let len = rand(7);
+            (0..len).map(|_| b'a' + rand(3) as u8).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    let s = SuffixKV::<LabelBitmap>::new(&keys);
+
+    for suffix in ["", "a", "ab", "cba", "aaaa", "d"] {
+        let want = keys
+            .iter()
+            .enumerate()
+            .filter(|(_, k)| k.ends_with(suffix.as_bytes()))
+            .map(|(i, k)| (i as i32, k.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(want.len() as i32, s.count_ends_with(suffix.as_bytes()));
+        assert_eq!(want, s.ends_with(suffix.as_bytes()), "suffix: {}", suffix);
+    }
+
+    for (i, k) in keys.iter().enumerate() {
+        assert_eq!(Some(i as i32), s.key_id(k));
+    }
+
+    Ok(())
+}
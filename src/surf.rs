@@ -0,0 +1,278 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::fst::Fst;
+use crate::fst::Step;
+use crate::int_vec::IntVec;
+
+/// What is stored for a key after its minimal distinguishing prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Suffix {
+    /// Nothing. A key having a stored prefix may be in the set.
+    None,
+
+    /// The lower bits of the hash of the key. It lowers the false positive rate of point
+    /// queries by about `2^-bits`.
+    Hash(u32),
+
+    /// The bits right after the prefix. It lowers the false positive rate of both point and
+    /// range queries.
+    Real(u32),
+}
+
+/// A succinct range filter(SuRF), an approximate membership filter that answers range queries.
+///
+/// It is a `Fst` of keys truncated at their minimal distinguishing prefixes, i.e., the shortest
+/// prefixes that are not a prefix of any other key. A key that is a prefix of another key is kept
+/// entirely. There is no false negative.
+///
+/// The suffix of a truncated key is stored by the index of its last label in `Step::Terminal`.
+pub struct Surf<BM>
+where BM: BitmapOps
+{
+    pub fst: Fst<BM>,
+    pub suffix: Suffix,
+    pub suffixes: IntVec,
+}
+
+impl<BM> Surf<BM>
+where BM: BuildBitmap
+{
+    /// Build from a sorted list of keys.
+    ///
+    /// A `Hash(0)` or `Real(0)` suffix stores nothing and is the same as `Suffix::None`.
+    #[allow(dead_code)]
+    pub fn new(keys: &[Vec<u8>], dense_levels: usize, suffix: Suffix) -> Self {
+        let suffix = match suffix {
+            Suffix::Hash(0) | Suffix::Real(0) => Suffix::None,
+            _ => suffix,
+        };
+
+        let mut keys = keys.to_vec();
+        keys.dedup();
+
+        let lcp = |a: &[u8], b: &[u8]| a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+
+        let prefix_lens = (0..keys.len())
+            .map(|i| {
+                let mut l = 0;
+                if i > 0 {
+                    l = l.max(lcp(&keys[i - 1], &keys[i]));
+                }
+                if i + 1 < keys.len() {
+                    l = l.max(lcp(&keys[i], &keys[i + 1]));
+                }
+                (l + 1).min(keys[i].len())
+            })
+            .collect::<Vec<_>>();
+
+        let prefixes = keys
+            .iter()
+            .zip(prefix_lens.iter())
+            .map(|(k, l)| k[..*l].to_vec())
+            .collect::<Vec<_>>();
+
+        let fst = Fst::<BM>::new(&prefixes, dense_levels);
+
+        let width = match suffix {
+            Suffix::None => 0,
+            Suffix::Hash(bits) | Suffix::Real(bits) => bits,
+        };
+        assert!(width <= 64, "suffix bits must not exceed 64: {}", width);
+
+        let mut suffixes = IntVec::new(width.max(1));
+
+        if width > 0 {
+            for (k, p) in keys.iter().zip(prefixes.iter()) {
+                if let Some(slot) = terminal(&fst, p) {
+                    while suffixes.len() <= slot as usize {
+                        suffixes.push(0);
+                    }
+                    suffixes.set(slot as usize, suffix_bits(suffix, k, p.len()));
+                }
+            }
+        }
+
+        Surf {
+            fst,
+            suffix,
+            suffixes,
+        }
+    }
+}
+
+impl<BM> Surf<BM>
+where BM: BitmapOps
+{
+    /// Returns `false` if `key` is definitely not in the set.
+    #[allow(dead_code)]
+    pub fn may_contain(&self, key: &[u8]) -> bool {
+        let mut node_id = 0;
+
+        for (i, c) in key.iter().enumerate() {
+            match self.fst.step(node_id, *c) {
+                Step::Child(child) => node_id = child,
+                Step::Missing => return false,
+                Step::Terminal(slot) => {
+                    return match self.suffix {
+                        Suffix::None => true,
+                        _ => self.stored(slot) == suffix_bits(self.suffix, key, i + 1),
+                    };
+                }
+            }
+        }
+
+        // A key that is a prefix of another key is stored entirely.
+        self.fst.is_prefix(node_id)
+    }
+
+    /// Returns `false` if there is definitely no key in `[lo, hi)`.
+    #[allow(dead_code)]
+    pub fn may_contain_range(&self, lo: &[u8], hi: &[u8]) -> bool {
+        if lo >= hi {
+            return false;
+        }
+
+        let mut prefix = vec![];
+        let found = self.seek(0, lo, true, &mut prefix);
+
+        let slot = match found {
+            None => return false,
+            Some(slot) => slot,
+        };
+
+        // Every key with this prefix is greater than or equal to `hi`.
+        if prefix.as_slice() >= hi {
+            return false;
+        }
+
+        if let (Suffix::Real(_), Some(slot)) = (self.suffix, slot) {
+            if hi.starts_with(&prefix) {
+                // The key starts with `hi[..prefix.len()]`, compare the next bits.
+                let want = suffix_bits(self.suffix, hi, prefix.len());
+                if self.stored(slot) > want {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Find in the subtree of `node_id` the first stored prefix of a key that may be greater than
+    /// or equal to `lo`, and append it to `prefix`, which is the path to `node_id`.
+    ///
+    /// With `tight`, `prefix` is a prefix of `lo`, otherwise it is already greater than `lo`.
+    /// It returns `None` if not found, otherwise the slot of the suffix if it is a truncated key.
+    fn seek(
+        &self,
+        node_id: i32,
+        lo: &[u8],
+        tight: bool,
+        prefix: &mut Vec<u8>,
+    ) -> Option<Option<i32>> {
+        let depth = prefix.len();
+
+        // The key ending at this node is `prefix`, it is less than `lo` only if `lo` is longer.
+        if self.fst.is_prefix(node_id) && (!tight || depth == lo.len()) {
+            return Some(None);
+        }
+
+        let mut from = if tight && depth < lo.len() {
+            lo[depth] as u32
+        } else {
+            0
+        };
+
+        while let Some((label, step)) = self.fst.next_label(node_id, from) {
+            let still_tight = tight && depth < lo.len() && label == lo[depth];
+            prefix.push(label);
+
+            match step {
+                Step::Child(child) => {
+                    if let Some(found) = self.seek(child, lo, still_tight, prefix) {
+                        return Some(found);
+                    }
+                }
+                Step::Terminal(slot) => {
+                    if !still_tight || self.may_reach(slot, lo, prefix.len()) {
+                        return Some(Some(slot));
+                    }
+                }
+                Step::Missing => unreachable!("next_label() returns only existing labels"),
+            }
+
+            prefix.pop();
+            from = label as u32 + 1;
+        }
+
+        None
+    }
+
+    /// Returns if the truncated key at `slot`, which has the same first `len` bytes as `lo`, may
+    /// be greater than or equal to `lo`.
+    fn may_reach(&self, slot: i32, lo: &[u8], len: usize) -> bool {
+        match self.suffix {
+            Suffix::Real(_) => self.stored(slot) >= suffix_bits(self.suffix, lo, len),
+            _ => true,
+        }
+    }
+
+    fn stored(&self, slot: i32) -> u64 {
+        self.suffixes.get(slot as usize)
+    }
+}
+
+/// Returns the slot of the truncated key `prefix` if it ends at a label without a child.
+fn terminal<BM: BitmapOps>(fst: &Fst<BM>, prefix: &[u8]) -> Option<i32> {
+    let mut node_id = 0;
+
+    for c in prefix.iter() {
+        match fst.step(node_id, *c) {
+            Step::Child(child) => node_id = child,
+            Step::Terminal(slot) => return Some(slot),
+            Step::Missing => return None,
+        }
+    }
+
+    None
+}
+
+/// Returns the suffix bits of `key` whose first `len` bytes are stored in the trie.
+fn suffix_bits(suffix: Suffix, key: &[u8], len: usize) -> u64 {
+    match suffix {
+        Suffix::None => 0,
+        Suffix::Hash(bits) => fnv1a(key) & mask(bits),
+        Suffix::Real(bits) => {
+            // The bytes after the prefix in big-endian, padded with 0.
+            let mut buf = [0u8; 8];
+            let rest = &key[len.min(key.len())..];
+            let n = rest.len().min(8);
+            buf[..n].copy_from_slice(&rest[..n]);
+
+            let v = u64::from_be_bytes(buf);
+            if bits == 0 {
+                0
+            } else {
+                v >> (64 - bits)
+            }
+        }
+    }
+}
+
+fn mask(bits: u32) -> u64 {
+    if bits == 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+fn fnv1a(key: &[u8]) -> u64 {
+    let mut h = 0xcbf29ce484222325u64;
+    for b in key.iter() {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
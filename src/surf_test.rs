@@ -0,0 +1,126 @@
+use crate::static_kv::LabelBitmap;
+use crate::surf::Suffix;
+use crate::surf::Surf;
+use crate::testing::lcg;
+use crate::testing::to_vecs;
+
+#[test]
+fn test_surf_example() -> anyhow::Result<()> {
+    let keys = to_vecs(&["far", "fast", "s", "top", "toy", "trie", "trip", "try"]);
+
+    let surf = Surf::<LabelBitmap>::new(&keys, 1, Suffix::None);
+
+    // Truncated: far, fas, s, top, toy, trie, trip, try
+    for k in keys.iter() {
+        assert!(surf.may_contain(k));
+    }
+    assert!(surf.may_contain(b"fasting"));
+    assert!(surf.may_contain(b"sun"));
+    assert!(!surf.may_contain(b"fa"));
+    assert!(!surf.may_contain(b"tr"));
+    assert!(!surf.may_contain(b"u"));
+
+    assert!(!surf.may_contain_range(b"fb", b"g"));
+    // "fast" is not in it, but it is not known without suffix bits.
+    assert!(surf.may_contain_range(b"fasz", b"g"));
+    assert!(surf.may_contain_range(b"a", b"fas"));
+    assert!(!surf.may_contain_range(b"a", b"far"));
+    assert!(!surf.may_contain_range(b"g", b"s"));
+    assert!(!surf.may_contain_range(b"tz", b"zzz"));
+    assert!(!surf.may_contain_range(b"tra", b"trie"));
+    assert!(surf.may_contain_range(b"tra", b"trif"));
+
+    let surf = Surf::<LabelBitmap>::new(&keys, 1, Suffix::Real(8));
+
+    // The byte after "fas" is 't'.
+    assert!(!surf.may_contain(b"fasa"));
+    assert!(surf.may_contain(b"fast"));
+    assert!(!surf.may_contain_range(b"fasu", b"g"));
+    assert!(!surf.may_contain_range(b"fas", b"fass"));
+    assert!(surf.may_contain_range(b"fa", b"fasu"));
+
+    let surf = Surf::<LabelBitmap>::new(&keys, 1, Suffix::Hash(16));
+    assert!(surf.may_contain(b"fast"));
+    assert!(!surf.may_contain(b"fasting"));
+
+    // 0 bits is the same as no suffix.
+    for suffix in [Suffix::Hash(0), Suffix::Real(0)] {
+        let surf = Surf::<LabelBitmap>::new(&keys, 1, suffix);
+        assert_eq!(Suffix::None, surf.suffix);
+
+        assert!(surf.may_contain(b"fast"));
+        assert!(surf.may_contain(b"fasting"));
+        assert!(!surf.may_contain(b"tr"));
+        assert!(surf.may_contain_range(b"fasz", b"g"));
+        assert!(!surf.may_contain_range(b"fb", b"g"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_surf() -> anyhow::Result<()> {
+    let mut rand = lcg(13);
+
+    let mut gen = |n: usize| {
+        (0..n)
+            .map(|_| {
+                let len = 1 + rand(12);
+                (0..len).map(|_| b'a' + rand(8) as u8).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut keys = gen(2000);
+    keys.sort();
+    keys.dedup();
+
+    let probes = gen(3000);
+
+    let mut bounds = gen(3000);
+    bounds.sort();
+
+    for suffix in [
+        Suffix::None,
+        Suffix::Hash(8),
+        Suffix::Real(8),
+        Suffix::Real(64),
+    ] {
+        for dense_levels in [0, 2] {
+            let surf = Surf::<LabelBitmap>::new(&keys, dense_levels, suffix);
+
+            for k in keys.iter() {
+                assert!(surf.may_contain(k), "{:?}: key: {:?}", suffix, k);
+            }
+
+            let mut false_positives = 0;
+            for k in probes.iter() {
+                let present = keys.binary_search(k).is_ok();
+                let got = surf.may_contain(k);
+                assert!(!present || got, "{:?}: key: {:?}", suffix, k);
+                false_positives += (!present && got) as i32;
+            }
+
+            if suffix != Suffix::None {
+                assert!(false_positives < 100, "{:?}: {}", suffix, false_positives);
+            }
+
+            for w in bounds.windows(2) {
+                let (lo, hi) = (&w[0], &w[1]);
+
+                let i = keys.partition_point(|k| k < lo);
+                let present = i < keys.len() && keys[i] < *hi;
+
+                assert!(
+                    !present || surf.may_contain_range(lo, hi),
+                    "{:?}: [{:?}, {:?})",
+                    suffix,
+                    lo,
+                    hi
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
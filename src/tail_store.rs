@@ -0,0 +1,99 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::int_vec::IntVec;
+
+/// A store of byte strings, such as the tails of the edges of a path-compressed trie, indexed by
+/// edge.
+///
+/// A string that is a suffix of another one is not stored again: it points into the longer one.
+/// Thus `bytes` is a list of fragments and `ends` has a `1` at the last byte of every fragment.
+///
+/// `edges` has a `1` at every edge with a non-empty string, and the string of such an edge `e` is
+/// the `count_ones(e)`-th one: it starts at `bytes[starts[i]]` and ends at the next end mark.
+pub struct TailStore<LBM>
+where LBM: BitmapOps
+{
+    pub edges: LBM,
+    pub starts: IntVec,
+    pub bytes: Vec<u8>,
+    pub ends: LBM,
+}
+
+impl<LBM> TailStore<LBM>
+where LBM: BuildBitmap
+{
+    /// Build from the string of every edge, an empty one for an edge without string.
+    #[allow(dead_code)]
+    pub fn new(strings: &[&[u8]]) -> Self {
+        // One more word to make `count_ones(len)` valid.
+        let mut edges = vec![0u64; (strings.len() >> 6) + 1];
+
+        let mut order = vec![];
+        for (i, s) in strings.iter().enumerate() {
+            if !s.is_empty() {
+                edges[i >> 6] |= 1 << (i & 63);
+                order.push(i);
+            }
+        }
+
+        // In descending order of the reversed strings, a string that is a suffix of others comes
+        // right after them.
+        order.sort_by(|a, b| strings[*b].iter().rev().cmp(strings[*a].iter().rev()));
+
+        let mut bytes = vec![];
+        let mut end_marks = vec![];
+        let mut starts = vec![0u64; strings.len()];
+
+        let mut last: &[u8] = &[];
+
+        for i in order {
+            let s = strings[i];
+
+            if !last.ends_with(s) {
+                bytes.extend_from_slice(s);
+                end_marks.push(bytes.len() - 1);
+                last = s;
+            }
+
+            starts[i] = (bytes.len() - s.len()) as u64;
+        }
+
+        let mut ends = vec![0u64; (bytes.len() >> 6) + 1];
+        for p in end_marks {
+            ends[p >> 6] |= 1 << (p & 63);
+        }
+
+        let starts = strings
+            .iter()
+            .zip(starts)
+            .filter(|(s, _)| !s.is_empty())
+            .map(|(_, start)| start)
+            .collect::<Vec<_>>();
+
+        TailStore {
+            edges: LBM::new(edges),
+            starts: IntVec::from_values(&starts),
+            bytes,
+            ends: LBM::new(ends),
+        }
+    }
+}
+
+impl<LBM> TailStore<LBM>
+where LBM: BitmapOps
+{
+    /// Returns the string of edge `edge`.
+    #[allow(dead_code)]
+    pub fn get(&self, edge: i32) -> &[u8] {
+        if self.edges.get_bit(edge) == 0 {
+            return &[];
+        }
+
+        let i = self.edges.count_ones(edge).0;
+        let start = self.starts.get(i as usize) as i32;
+
+        let end = self.ends.select_ith_one(self.ends.count_ones(start).0) + 1;
+
+        &self.bytes[start as usize..end as usize]
+    }
+}
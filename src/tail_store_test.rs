@@ -0,0 +1,33 @@
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::static_kv::LabelBitmap;
+use crate::tail_store::TailStore;
+
+#[test]
+fn test_tail_store() -> anyhow::Result<()> {
+    let strings: Vec<&[u8]> = vec![b"", b"abc", b"bc", b"", b"xbc", b"c", b"abc", b"d"];
+
+    let s = TailStore::<LabelBitmap>::new(&strings);
+    let rle = TailStore::<RleBitmap>::new(&strings);
+
+    for (i, want) in strings.iter().enumerate() {
+        assert_eq!(*want, s.get(i as i32), "edge: {}", i);
+        assert_eq!(*want, rle.get(i as i32), "edge: {}", i);
+    }
+
+    // "bc", "c" and the second "abc" point into "abc" or "xbc".
+    assert_eq!(b"xbcabcd".len(), s.bytes.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_tail_store_empty() -> anyhow::Result<()> {
+    let s = TailStore::<LabelBitmap>::new(&[]);
+    assert!(s.bytes.is_empty());
+
+    let s = TailStore::<LabelBitmap>::new(&[b"", b""]);
+    assert_eq!(b"", s.get(0));
+    assert_eq!(b"", s.get(1));
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! Helpers shared by tests.
+
+/// A small set of sorted keys sharing prefixes at several depths.
+pub fn keys() -> Vec<Vec<u8>> {
+    let x = vec![
+        "A",
+        "Aani",
+        "Aaron",
+        "Aaronic",
+        "Aaronical",
+        "Aaronite",
+        "Aaronitic",
+        "Aaru",
+        "Ab",
+        "Ababdeh",
+        "Ababua",
+        "Abadite",
+    ];
+
+    x.iter().map(|x| x.as_bytes().to_vec()).collect()
+}
+
+/// Returns a generator of pseudo random numbers less than the argument.
+///
+/// It is a simple LCG, to generate the same data in every run without external dependency.
+pub fn lcg(seed: u64) -> impl FnMut(u64) -> u64 {
+    let mut x = seed;
+    move |m: u64| -> u64 {
+        x = x
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (x >> 33) % m
+    }
+}
+
+/// Converts strings to byte keys.
+pub fn to_vecs(v: &[&str]) -> Vec<Vec<u8>> {
+    v.iter().map(|x| x.as_bytes().to_vec()).collect()
+}
@@ -0,0 +1,156 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::bitmap::indexed_bitmap::IndexedBitmap;
+use crate::bitmap::rank::RankIndex64;
+use crate::bitmap::select::SelectIndex32;
+use crate::tree::rmm::RangeMinMax;
+
+/// An ordinal tree in balanced parentheses representation.
+///
+/// The tree is walked in depth first order, an open parenthesis `1` is written when entering a
+/// node and a close parenthesis `0` is written when leaving it.
+/// A node is identified by the position of its open parenthesis.
+///
+/// E.g.:
+///     root
+///     ├── a
+///     │   └── c
+///     └── b
+///     ( ( ( ) ) ( ) )
+///     r a c     b
+pub struct BpTree {
+    pub bitmap: IndexedBitmap<SelectIndex32<RankIndex64>>,
+    pub rmm: RangeMinMax,
+
+    /// The number of parentheses.
+    pub len: i32,
+}
+
+impl BpTree {
+    /// Build from a bitmap of `len` balanced parentheses.
+    #[allow(dead_code)]
+    pub fn new(words: Vec<u64>, len: i32) -> Self {
+        let mut words = words;
+
+        // One more word to make `count_ones(len)` valid.
+        words.resize(((len >> 6) + 1) as usize, 0);
+
+        let rmm = RangeMinMax::new(&words, len);
+
+        BpTree {
+            bitmap: IndexedBitmap::new(words),
+            rmm,
+            len,
+        }
+    }
+
+    /// Build from a string of `(` and `)`.
+    #[allow(dead_code)]
+    pub fn from_parens(parens: &str) -> Self {
+        let mut words = vec![];
+
+        for (i, c) in parens.bytes().enumerate() {
+            if i & 63 == 0 {
+                words.push(0);
+            }
+            if c == b'(' {
+                words[i >> 6] |= 1 << (i & 63);
+            }
+        }
+
+        Self::new(words, parens.len() as i32)
+    }
+
+    /// Returns the number of nodes.
+    #[allow(dead_code)]
+    pub fn nodes(&self) -> i32 {
+        self.len / 2
+    }
+
+    #[allow(dead_code)]
+    pub fn is_open(&self, i: i32) -> bool {
+        self.bitmap.get_bit(i) == 1
+    }
+
+    /// Returns the position of the close parenthesis matching the open parenthesis at `i`.
+    #[allow(dead_code)]
+    pub fn find_close(&self, i: i32) -> i32 {
+        self.rmm.fwd_search(&self.bitmap.words, i, -1).unwrap()
+    }
+
+    /// Returns the position of the open parenthesis matching the close parenthesis at `i`.
+    #[allow(dead_code)]
+    pub fn find_open(&self, i: i32) -> i32 {
+        self.rmm.bwd_search(&self.bitmap.words, i, 0).unwrap() + 1
+    }
+
+    /// Returns the position of the open parenthesis of the closest pair enclosing the open
+    /// parenthesis at `i`, or `None` if `i` is the root.
+    #[allow(dead_code)]
+    pub fn enclose(&self, i: i32) -> Option<i32> {
+        self.rmm
+            .bwd_search(&self.bitmap.words, i, -2)
+            .map(|j| j + 1)
+    }
+
+    /// Returns the parent of node `i`, or `None` if `i` is the root.
+    #[allow(dead_code)]
+    pub fn parent(&self, i: i32) -> Option<i32> {
+        self.enclose(i)
+    }
+
+    /// Returns the first child of node `i`, or `None` if `i` is a leaf.
+    #[allow(dead_code)]
+    pub fn first_child(&self, i: i32) -> Option<i32> {
+        if i + 1 < self.len && self.is_open(i + 1) {
+            Some(i + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the next sibling of node `i`, or `None` if `i` is the last child.
+    #[allow(dead_code)]
+    pub fn next_sibling(&self, i: i32) -> Option<i32> {
+        let j = self.find_close(i) + 1;
+
+        if j < self.len && self.is_open(j) {
+            Some(j)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of nodes in the subtree rooted at node `i`, including `i`.
+    #[allow(dead_code)]
+    pub fn subtree_size(&self, i: i32) -> i32 {
+        (self.find_close(i) - i + 1) / 2
+    }
+
+    /// Returns the depth of node `i`. The depth of the root is 0.
+    #[allow(dead_code)]
+    pub fn depth(&self, i: i32) -> i32 {
+        self.rmm.excess(&self.bitmap.words, i) - 1
+    }
+
+    /// Returns the ancestor of node `i` that is `d` levels above it, or `None` if `d` is greater
+    /// than the depth of `i`. `level_ancestor(i, 0)` is `i`.
+    #[allow(dead_code)]
+    pub fn level_ancestor(&self, i: i32, d: i32) -> Option<i32> {
+        self.rmm
+            .bwd_search(&self.bitmap.words, i, -d - 1)
+            .map(|j| j + 1)
+    }
+
+    /// Returns the preorder rank of node `i`. The rank of the root is 0.
+    #[allow(dead_code)]
+    pub fn preorder(&self, i: i32) -> i32 {
+        self.bitmap.count_ones(i).0
+    }
+
+    /// Returns the node with preorder rank `k`.
+    #[allow(dead_code)]
+    pub fn node(&self, k: i32) -> i32 {
+        self.bitmap.select_ith_one(k)
+    }
+}
@@ -0,0 +1,101 @@
+use crate::tree::bp::BpTree;
+use crate::tree::testing::random_parens;
+use crate::tree::testing::NaiveTree;
+
+fn trees() -> Vec<String> {
+    vec![
+        "()".to_string(),
+        "(())".to_string(),
+        "(()())".to_string(),
+        "((()())(()))".to_string(),
+        random_parens(1, 100, 5),
+        random_parens(2, 1000, 8),
+        random_parens(3, 1000, 2),
+        random_parens(4, 3000, 5),
+    ]
+}
+
+#[test]
+fn test_bp_tree_example() -> anyhow::Result<()> {
+    //  0 ( root
+    //  1   ( a
+    //  2     ( c
+    //  3     )
+    //  4   )
+    //  5   ( b
+    //  6   )
+    //  7 )
+    let t = BpTree::from_parens("((())())");
+
+    assert_eq!(4, t.nodes());
+    assert_eq!(7, t.find_close(0));
+    assert_eq!(4, t.find_close(1));
+    assert_eq!(1, t.find_open(4));
+    assert_eq!(None, t.enclose(0));
+    assert_eq!(Some(1), t.parent(2));
+    assert_eq!(Some(0), t.parent(5));
+    assert_eq!(Some(1), t.first_child(0));
+    assert_eq!(None, t.first_child(2));
+    assert_eq!(Some(5), t.next_sibling(1));
+    assert_eq!(None, t.next_sibling(5));
+    assert_eq!(4, t.subtree_size(0));
+    assert_eq!(2, t.subtree_size(1));
+    assert_eq!(2, t.depth(2));
+    assert_eq!(Some(0), t.level_ancestor(2, 2));
+    assert_eq!(None, t.level_ancestor(2, 3));
+    assert_eq!(3, t.preorder(5));
+    assert_eq!(5, t.node(3));
+
+    Ok(())
+}
+
+#[test]
+fn test_bp_tree() -> anyhow::Result<()> {
+    for parens in trees() {
+        let t = BpTree::from_parens(&parens);
+        let naive = NaiveTree::new(&parens);
+
+        for (k, i) in naive.nodes().into_iter().enumerate() {
+            let iu = i as usize;
+            let close = naive.close[iu];
+
+            assert_eq!(close, t.find_close(i), "find_close({})", i);
+            assert_eq!(i, t.find_open(close), "find_open({})", close);
+
+            let parent = Some(naive.parent[iu]).filter(|x| *x >= 0);
+            assert_eq!(parent, t.enclose(i), "enclose({})", i);
+            assert_eq!(parent, t.parent(i), "parent({})", i);
+
+            let children = &naive.children[iu];
+            assert_eq!(children.first().copied(), t.first_child(i));
+
+            for w in children.windows(2) {
+                assert_eq!(Some(w[1]), t.next_sibling(w[0]));
+            }
+            if let Some(last) = children.last() {
+                assert_eq!(None, t.next_sibling(*last));
+            }
+
+            assert_eq!((close - i + 1) / 2, t.subtree_size(i));
+            assert_eq!(naive.depth[iu], t.depth(i), "depth({})", i);
+
+            let mut anc = i;
+            for d in 0..=naive.depth[iu] {
+                assert_eq!(
+                    Some(anc),
+                    t.level_ancestor(i, d),
+                    "level_ancestor({}, {})",
+                    i,
+                    d
+                );
+                anc = naive.parent[anc as usize];
+            }
+            assert_eq!(None, t.level_ancestor(i, naive.depth[iu] + 1));
+
+            assert_eq!(k as i32, t.preorder(i));
+            assert_eq!(i, t.node(k as i32));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::bitmap::indexed_bitmap::IndexedBitmap;
+use crate::bitmap::rank::RankIndex64;
+use crate::bitmap::select::SelectIndex32;
+use crate::tree::rmm::RangeMinMax;
+
+/// An ordinal tree in depth-first unary degree sequence(DFUDS) representation.
+///
+/// The nodes are walked in preorder, and a node of degree `d` is written as `d` open parentheses
+/// `1` followed by a close parenthesis `0`. An extra open parenthesis is prepended to make the
+/// sequence balanced. A node is identified by the position of the first parenthesis of it.
+///
+/// E.g.:
+///     root
+///     ├── a
+///     │   └── c
+///     └── b
+///     ( ( ( ) ( ) ) )
+///       r     a   c b
+pub struct DfudsTree {
+    pub bitmap: IndexedBitmap<SelectIndex32<RankIndex64>>,
+    pub rmm: RangeMinMax,
+
+    /// The number of parentheses.
+    pub len: i32,
+}
+
+impl DfudsTree {
+    /// Build from a bitmap of `len` parentheses in DFUDS order, including the leading `1`.
+    #[allow(dead_code)]
+    pub fn new(words: Vec<u64>, len: i32) -> Self {
+        let mut words = words;
+
+        // One more word to make `count_ones(len)` valid.
+        words.resize(((len >> 6) + 1) as usize, 0);
+
+        let rmm = RangeMinMax::new(&words, len);
+
+        DfudsTree {
+            bitmap: IndexedBitmap::new(words),
+            rmm,
+            len,
+        }
+    }
+
+    /// Build from the degrees of all nodes in preorder.
+    #[allow(dead_code)]
+    pub fn from_degrees(degrees: &[i32]) -> Self {
+        let len = degrees.len() * 2;
+        let mut words = vec![0u64; (len >> 6) + 1];
+
+        // The leading open parenthesis.
+        words[0] = 1;
+
+        let mut i = 1;
+        for d in degrees.iter() {
+            for _ in 0..*d {
+                words[i >> 6] |= 1 << (i & 63);
+                i += 1;
+            }
+            // The close parenthesis is 0 already.
+            i += 1;
+        }
+
+        Self::new(words, len as i32)
+    }
+
+    /// Returns the number of nodes.
+    #[allow(dead_code)]
+    pub fn nodes(&self) -> i32 {
+        self.len / 2
+    }
+
+    /// Returns the root node.
+    #[allow(dead_code)]
+    pub fn root(&self) -> i32 {
+        1
+    }
+
+    /// Returns the number of children of node `v`.
+    #[allow(dead_code)]
+    pub fn degree(&self, v: i32) -> i32 {
+        self.next_close(v) - v
+    }
+
+    #[allow(dead_code)]
+    pub fn is_leaf(&self, v: i32) -> bool {
+        self.bitmap.get_bit(v) == 0
+    }
+
+    /// Returns the i-th(0-based) child of node `v`, or `None` if `v` does not have that many
+    /// children.
+    #[allow(dead_code)]
+    pub fn child(&self, v: i32, i: i32) -> Option<i32> {
+        if i < 0 || i >= self.degree(v) {
+            return None;
+        }
+
+        // The open parenthesis of the i-th child is the i-th one counted from the end of the
+        // description of `v`.
+        let open = self.next_close(v) - i - 1;
+        Some(self.find_close(open) + 1)
+    }
+
+    /// Returns the parent of node `v`, or `None` if `v` is the root.
+    #[allow(dead_code)]
+    pub fn parent(&self, v: i32) -> Option<i32> {
+        if v == self.root() {
+            return None;
+        }
+
+        let open = self.find_open(v - 1);
+        Some(self.description_start(open))
+    }
+
+    /// Returns the index of node `v` among its siblings, or `None` if `v` is the root.
+    #[allow(dead_code)]
+    pub fn child_rank(&self, v: i32) -> Option<i32> {
+        if v == self.root() {
+            return None;
+        }
+
+        let open = self.find_open(v - 1);
+        Some(self.next_close(open) - open - 1)
+    }
+
+    /// Returns the number of nodes in the subtree rooted at node `v`, including `v`.
+    #[allow(dead_code)]
+    pub fn subtree_size(&self, v: i32) -> i32 {
+        // The subtree ends where the excess first drops below the excess before `v`.
+        let end = self.rmm.fwd_search(&self.bitmap.words, v - 1, -1).unwrap();
+        (end - v) / 2 + 1
+    }
+
+    /// Returns the preorder rank of node `v`. The rank of the root is 0.
+    #[allow(dead_code)]
+    pub fn preorder(&self, v: i32) -> i32 {
+        self.bitmap.count_zeros(v).0
+    }
+
+    /// Returns the node with preorder rank `k`.
+    #[allow(dead_code)]
+    pub fn node(&self, k: i32) -> i32 {
+        if k == 0 {
+            self.root()
+        } else {
+            self.bitmap.select_ith_zero(k - 1) + 1
+        }
+    }
+
+    /// Returns the position of the first `0` at or after `i`.
+    fn next_close(&self, i: i32) -> i32 {
+        self.bitmap.select_ith_zero(self.bitmap.count_zeros(i).0)
+    }
+
+    /// Returns the start of the node description that contains position `i`.
+    fn description_start(&self, i: i32) -> i32 {
+        self.node(self.bitmap.count_zeros(i).0)
+    }
+
+    fn find_close(&self, i: i32) -> i32 {
+        self.rmm.fwd_search(&self.bitmap.words, i, -1).unwrap()
+    }
+
+    fn find_open(&self, i: i32) -> i32 {
+        self.rmm.bwd_search(&self.bitmap.words, i, 0).unwrap() + 1
+    }
+}
@@ -0,0 +1,101 @@
+use crate::tree::dfuds::DfudsTree;
+use crate::tree::testing::random_parens;
+use crate::tree::testing::NaiveTree;
+
+#[test]
+fn test_dfuds_tree_example() -> anyhow::Result<()> {
+    //  0 (
+    //  1 ( root
+    //  2 (
+    //  3 )
+    //  4 ( a
+    //  5 )
+    //  6 ) c
+    //  7 ) b
+    let t = DfudsTree::from_degrees(&[2, 1, 0, 0]);
+
+    assert_eq!(4, t.nodes());
+    assert_eq!(1, t.root());
+    assert_eq!(2, t.degree(1));
+    assert_eq!(1, t.degree(4));
+    assert_eq!(0, t.degree(7));
+    assert!(t.is_leaf(6));
+    assert!(!t.is_leaf(4));
+    assert_eq!(Some(4), t.child(1, 0));
+    assert_eq!(Some(7), t.child(1, 1));
+    assert_eq!(None, t.child(1, 2));
+    assert_eq!(Some(6), t.child(4, 0));
+    assert_eq!(None, t.parent(1));
+    assert_eq!(Some(1), t.parent(7));
+    assert_eq!(Some(4), t.parent(6));
+    assert_eq!(None, t.child_rank(1));
+    assert_eq!(Some(1), t.child_rank(7));
+    assert_eq!(4, t.subtree_size(1));
+    assert_eq!(2, t.subtree_size(4));
+    assert_eq!(1, t.subtree_size(7));
+    assert_eq!(2, t.preorder(6));
+    assert_eq!(6, t.node(2));
+
+    Ok(())
+}
+
+#[test]
+fn test_dfuds_tree() -> anyhow::Result<()> {
+    let trees = vec![
+        "()".to_string(),
+        "(()())".to_string(),
+        random_parens(1, 100, 5),
+        random_parens(2, 1000, 8),
+        random_parens(3, 1000, 2),
+        random_parens(4, 3000, 5),
+    ];
+
+    for parens in trees {
+        let naive = NaiveTree::new(&parens);
+        let nodes = naive.nodes();
+
+        // preorder rank of every node, by its position in `parens`
+        let mut rank = vec![-1; parens.len()];
+        for (k, i) in nodes.iter().enumerate() {
+            rank[*i as usize] = k as i32;
+        }
+
+        let degrees = nodes
+            .iter()
+            .map(|i| naive.children[*i as usize].len() as i32)
+            .collect::<Vec<_>>();
+
+        let t = DfudsTree::from_degrees(&degrees);
+        assert_eq!(nodes.len() as i32, t.nodes());
+
+        for (k, i) in nodes.iter().enumerate() {
+            let iu = *i as usize;
+            let v = t.node(k as i32);
+            assert_eq!(k as i32, t.preorder(v));
+
+            let children = &naive.children[iu];
+            assert_eq!(children.len() as i32, t.degree(v), "degree({})", v);
+            assert_eq!(children.is_empty(), t.is_leaf(v));
+
+            for (ci, c) in children.iter().enumerate() {
+                let cv = t.node(rank[*c as usize]);
+                assert_eq!(Some(cv), t.child(v, ci as i32), "child({}, {})", v, ci);
+                assert_eq!(Some(ci as i32), t.child_rank(cv));
+            }
+            assert_eq!(None, t.child(v, children.len() as i32));
+
+            let parent = naive.parent[iu];
+            let want = if parent < 0 {
+                None
+            } else {
+                Some(t.node(rank[parent as usize]))
+            };
+            assert_eq!(want, t.parent(v), "parent({})", v);
+
+            let size = (naive.close[iu] - i + 1) / 2;
+            assert_eq!(size, t.subtree_size(v), "subtree_size({})", v);
+        }
+    }
+
+    Ok(())
+}
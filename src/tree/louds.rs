@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+
+/// An ordinal tree in level-order unary degree sequence(LOUDS) representation, the same encoding
+/// `StaticKV` uses for its `label_bitmap`.
+///
+/// The nodes are walked in breadth first order, and a node of degree `d` is written as `d` `0`
+/// followed by a `1`. A node is identified by its breadth first order, the root is 0.
+/// The i-th `0` stands for the node `i + 1`.
+///
+/// E.g.:
+///     root
+///     ├── a
+///     │   └── c
+///     └── b
+///     0 0 1 0 1 1 1
+///     r     a   b c
+pub struct LoudsTree<BM>
+where BM: BitmapOps
+{
+    pub bitmap: BM,
+
+    /// The number of nodes.
+    pub nodes: i32,
+}
+
+impl<BM> LoudsTree<BM>
+where BM: BuildBitmap
+{
+    /// Build from the degrees of all nodes in breadth first order.
+    ///
+    /// An empty `degrees` builds an empty tree, without any node.
+    #[allow(dead_code)]
+    pub fn from_degrees(degrees: &[i32]) -> Self {
+        // Every node has a `1` and every node except the root has a `0`.
+        let len = (degrees.len() * 2).saturating_sub(1);
+
+        // One more word to make `count_ones(len)` valid.
+        let mut words = vec![0u64; (len >> 6) + 1];
+
+        let mut i = 0;
+        for d in degrees.iter() {
+            i += *d as usize;
+            words[i >> 6] |= 1 << (i & 63);
+            i += 1;
+        }
+
+        LoudsTree {
+            bitmap: BM::new(words),
+            nodes: degrees.len() as i32,
+        }
+    }
+
+    /// Build from any ordinal tree, given its root and a function returning the children of a
+    /// node in order.
+    ///
+    /// It returns the tree and the original nodes indexed by node id, to which the data of a node
+    /// can be attached.
+    #[allow(dead_code)]
+    pub fn build<N, F, I>(root: N, children: F) -> (Self, Vec<N>)
+    where
+        F: Fn(&N) -> I,
+        I: IntoIterator<Item = N>,
+    {
+        let mut degrees = vec![];
+        let mut order = vec![];
+
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+
+        while let Some(n) = queue.pop_front() {
+            let before = queue.len();
+            queue.extend(children(&n));
+
+            degrees.push((queue.len() - before) as i32);
+            order.push(n);
+        }
+
+        (Self::from_degrees(&degrees), order)
+    }
+}
+
+impl<BM> LoudsTree<BM>
+where BM: BitmapOps
+{
+    #[allow(dead_code)]
+    pub fn root(&self) -> i32 {
+        0
+    }
+
+    /// Returns the parent of node `v`, or `None` if `v` is the root.
+    #[allow(dead_code)]
+    pub fn parent(&self, v: i32) -> Option<i32> {
+        if v == 0 {
+            return None;
+        }
+
+        // The count of `1` before the `0` of `v` is the id of the parent.
+        Some(self.bitmap.count_ones(self.bitmap.select_ith_zero(v - 1)).0)
+    }
+
+    /// Returns the number of children of node `v`.
+    #[allow(dead_code)]
+    pub fn degree(&self, v: i32) -> i32 {
+        self.bitmap.select_ith_one(v) - self.start(v)
+    }
+
+    #[allow(dead_code)]
+    pub fn is_leaf(&self, v: i32) -> bool {
+        self.bitmap.get_bit(self.start(v)) == 1
+    }
+
+    /// Returns the i-th(0-based) child of node `v`, or `None` if `v` does not have that many
+    /// children.
+    #[allow(dead_code)]
+    pub fn child(&self, v: i32, i: i32) -> Option<i32> {
+        if i < 0 || i >= self.degree(v) {
+            return None;
+        }
+        Some(self.bitmap.count_zeros(self.start(v) + i + 1).0)
+    }
+
+    /// Returns the first child of node `v`, or `None` if `v` is a leaf.
+    #[allow(dead_code)]
+    pub fn first_child(&self, v: i32) -> Option<i32> {
+        self.child(v, 0)
+    }
+
+    /// Returns the last child of node `v`, or `None` if `v` is a leaf.
+    #[allow(dead_code)]
+    pub fn last_child(&self, v: i32) -> Option<i32> {
+        self.child(v, self.degree(v) - 1)
+    }
+
+    /// Returns the next sibling of node `v`, or `None` if `v` is the last child or the root.
+    #[allow(dead_code)]
+    pub fn next_sibling(&self, v: i32) -> Option<i32> {
+        if v == 0 {
+            return None;
+        }
+
+        let p = self.bitmap.select_ith_zero(v - 1);
+        if self.bitmap.get_bit(p + 1) == 0 {
+            Some(v + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the previous sibling of node `v`, or `None` if `v` is the first child or the root.
+    #[allow(dead_code)]
+    pub fn prev_sibling(&self, v: i32) -> Option<i32> {
+        if v == 0 {
+            return None;
+        }
+
+        let p = self.bitmap.select_ith_zero(v - 1);
+        if p > 0 && self.bitmap.get_bit(p - 1) == 0 {
+            Some(v - 1)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the position of the first bit of node `v`.
+    fn start(&self, v: i32) -> i32 {
+        debug_assert!(v < self.nodes);
+
+        if v == 0 {
+            0
+        } else {
+            self.bitmap.select_ith_one(v - 1) + 1
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::static_kv::LabelBitmap;
+use crate::tree::louds::LoudsTree;
+use crate::tree::testing::random_parens;
+use crate::tree::testing::NaiveTree;
+
+#[test]
+fn test_louds_tree_example() -> anyhow::Result<()> {
+    // root: 0 0 1
+    // a:    0 1
+    // b:    1
+    // c:    1
+    let t = LoudsTree::<LabelBitmap>::from_degrees(&[2, 1, 0, 0]);
+
+    assert_eq!(4, t.nodes);
+    assert_eq!(0, t.root());
+    assert_eq!(None, t.parent(0));
+    assert_eq!(Some(0), t.parent(2));
+    assert_eq!(Some(1), t.parent(3));
+    assert_eq!(2, t.degree(0));
+    assert_eq!(0, t.degree(3));
+    assert!(t.is_leaf(2));
+    assert!(!t.is_leaf(1));
+    assert_eq!(Some(2), t.child(0, 1));
+    assert_eq!(None, t.child(0, 2));
+    assert_eq!(Some(1), t.first_child(0));
+    assert_eq!(Some(2), t.last_child(0));
+    assert_eq!(None, t.first_child(3));
+    assert_eq!(Some(2), t.next_sibling(1));
+    assert_eq!(None, t.next_sibling(2));
+    assert_eq!(Some(1), t.prev_sibling(2));
+    assert_eq!(None, t.prev_sibling(1));
+    assert_eq!(None, t.prev_sibling(3));
+
+    Ok(())
+}
+
+#[test]
+fn test_louds_tree_build() -> anyhow::Result<()> {
+    // A category hierarchy.
+    let children = |n: &&str| -> Vec<&str> {
+        match *n {
+            "all" => vec!["books", "music"],
+            "books" => vec!["fiction", "science"],
+            "music" => vec!["jazz"],
+            _ => vec![],
+        }
+    };
+
+    let (t, names) = LoudsTree::<LabelBitmap>::build("all", children);
+
+    assert_eq!(
+        vec!["all", "books", "music", "fiction", "science", "jazz"],
+        names
+    );
+    assert_eq!(Some(4), t.child(1, 1));
+    assert_eq!(Some(2), t.parent(5));
+
+    Ok(())
+}
+
+#[test]
+fn test_louds_tree() -> anyhow::Result<()> {
+    check_louds_tree::<LabelBitmap>();
+    check_louds_tree::<RleBitmap>();
+
+    Ok(())
+}
+
+fn check_louds_tree<BM: BuildBitmap>() {
+    let trees = vec![
+        "()".to_string(),
+        "(()())".to_string(),
+        random_parens(1, 100, 5),
+        random_parens(2, 1000, 8),
+        random_parens(3, 1000, 2),
+    ];
+
+    for parens in trees {
+        let naive = NaiveTree::new(&parens);
+
+        let (t, order) = LoudsTree::<BM>::build(0, |i: &i32| naive.children[*i as usize].clone());
+        assert_eq!(order.len() as i32, t.nodes);
+
+        // node id by the position in `parens`
+        let mut id = vec![-1; parens.len()];
+        for (v, i) in order.iter().enumerate() {
+            id[*i as usize] = v as i32;
+        }
+
+        for (v, i) in order.iter().enumerate() {
+            let v = v as i32;
+            let iu = *i as usize;
+
+            let parent = naive.parent[iu];
+            let want = Some(parent).filter(|p| *p >= 0).map(|p| id[p as usize]);
+            assert_eq!(want, t.parent(v), "parent({})", v);
+
+            let children = naive.children[iu]
+                .iter()
+                .map(|c| id[*c as usize])
+                .collect::<Vec<_>>();
+
+            assert_eq!(children.len() as i32, t.degree(v));
+            assert_eq!(children.is_empty(), t.is_leaf(v));
+            assert_eq!(children.first().copied(), t.first_child(v));
+            assert_eq!(children.last().copied(), t.last_child(v));
+
+            for (k, c) in children.iter().enumerate() {
+                assert_eq!(Some(*c), t.child(v, k as i32));
+                assert_eq!(children.get(k + 1).copied(), t.next_sibling(*c));
+
+                let prev = if k == 0 { None } else { Some(children[k - 1]) };
+                assert_eq!(prev, t.prev_sibling(*c));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_louds_tree_empty() -> anyhow::Result<()> {
+    let t = LoudsTree::<LabelBitmap>::from_degrees(&[]);
+    assert_eq!(0, t.nodes);
+
+    let t = LoudsTree::<RleBitmap>::from_degrees(&[]);
+    assert_eq!(0, t.nodes);
+
+    // a single root
+    let t = LoudsTree::<LabelBitmap>::from_degrees(&[0]);
+    assert_eq!(1, t.nodes);
+    assert!(t.is_leaf(0));
+    assert_eq!(0, t.degree(0));
+    assert_eq!(None, t.first_child(0));
+    assert_eq!(None, t.parent(0));
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+pub mod bp;
+#[cfg(test)]
+mod bp_test;
+pub mod dfuds;
+#[cfg(test)]
+mod dfuds_test;
+pub mod louds;
+#[cfg(test)]
+mod louds_test;
+pub mod rmm;
+#[cfg(test)]
+mod rmm_test;
+pub mod rmq;
+#[cfg(test)]
+mod rmq_test;
+#[cfg(test)]
+pub mod testing;
@@ -0,0 +1,251 @@
+use crate::bitmap::context::Context;
+use crate::static_kv::CTX;
+
+/// A range min-max tree to speed up searching by excess in a bitmap of balanced parentheses, where
+/// `1` is an open parenthesis and `0` is a close parenthesis.
+///
+/// The excess `E(i)` is the count of `1` minus the count of `0` in `[0, i]`, and `E(-1)` is 0.
+///
+/// Every word of the bitmap is a block. A complete binary tree over the blocks stores the min and
+/// max excess in every node. Since the excess changes by 1 at every position, a block contains a
+/// position with excess `t` if and only if `min <= t <= max`, which is used to skip blocks.
+///
+/// Like a rank index, it does not own the bitmap: the words are passed in to every query.
+#[derive(Clone)]
+pub struct RangeMinMax {
+    /// The excess before every block, i.e., `E(64 * b - 1)` for block `b`.
+    /// It has one more element than the number of blocks: the last one is the excess of the
+    /// entire bitmap.
+    pub block_excess: Vec<i32>,
+
+    /// The min excess of every tree node. The tree is stored in an array with the root at 1 and
+    /// the children of node `v` at `2v` and `2v+1`. Leaves start at `leaf_start`.
+    pub min: Vec<i32>,
+
+    /// The max excess of every tree node.
+    pub max: Vec<i32>,
+
+    /// The index of the first leaf, which is a power of 2.
+    pub leaf_start: usize,
+
+    /// The number of parentheses.
+    pub len: i32,
+
+    pub ctx: &'static Context,
+}
+
+impl RangeMinMax {
+    pub fn new(words: &[u64], len: i32) -> Self {
+        let blocks = ((len + 63) >> 6) as usize;
+        let leaf_start = blocks.next_power_of_two();
+
+        let mut block_excess = Vec::with_capacity(blocks + 1);
+        let mut min = vec![i32::MAX; leaf_start * 2];
+        let mut max = vec![i32::MIN; leaf_start * 2];
+
+        let mut e = 0;
+        for b in 0..blocks {
+            block_excess.push(e);
+
+            let end = len.min(((b + 1) << 6) as i32);
+            for i in ((b << 6) as i32)..end {
+                e += bit_excess(words, i);
+                min[leaf_start + b] = min[leaf_start + b].min(e);
+                max[leaf_start + b] = max[leaf_start + b].max(e);
+            }
+        }
+        block_excess.push(e);
+
+        for v in (1..leaf_start).rev() {
+            min[v] = min[2 * v].min(min[2 * v + 1]);
+            max[v] = max[2 * v].max(max[2 * v + 1]);
+        }
+
+        RangeMinMax {
+            block_excess,
+            min,
+            max,
+            leaf_start,
+            len,
+            ctx: &CTX,
+        }
+    }
+
+    /// Returns `E(i)`, the excess in `[0, i]`. `i` can be -1.
+    pub fn excess(&self, words: &[u64], i: i32) -> i32 {
+        if i < 0 {
+            return 0;
+        }
+
+        let w = words[(i >> 6) as usize] & self.ctx.masks.mask_upto[(i & 63) as usize];
+        let ones = w.count_ones() as i32;
+
+        self.block_excess[(i >> 6) as usize] + 2 * ones - ((i & 63) + 1)
+    }
+
+    /// Returns the smallest `j > i` such that `E(j) = E(i) + d`.
+    pub fn fwd_search(&self, words: &[u64], i: i32, d: i32) -> Option<i32> {
+        let t = self.excess(words, i) + d;
+        let from = i + 1;
+
+        if from >= self.len {
+            return None;
+        }
+
+        let b = (from >> 6) as usize;
+        if let Some(j) = self.scan_fwd(words, from, self.excess(words, i), t) {
+            return Some(j);
+        }
+
+        let b = self.next_block(b, t)?;
+        self.scan_fwd(words, (b << 6) as i32, self.block_excess[b], t)
+    }
+
+    /// Returns the largest `j < i` such that `E(j) = E(i) + d`. The result can be -1.
+    pub fn bwd_search(&self, words: &[u64], i: i32, d: i32) -> Option<i32> {
+        let t = self.excess(words, i) + d;
+
+        if i > 0 {
+            let from = i - 1;
+            let b = (from >> 6) as usize;
+
+            if let Some(j) = self.scan_bwd(words, from, self.excess(words, from), t) {
+                return Some(j);
+            }
+
+            if let Some(b) = self.prev_block(b, t) {
+                let from = (((b + 1) << 6) as i32).min(self.len) - 1;
+                return self.scan_bwd(words, from, self.block_excess[b + 1], t);
+            }
+        }
+
+        if t == 0 {
+            Some(-1)
+        } else {
+            None
+        }
+    }
+
+    /// Scan from position `from` to the end of its block, for the first position with excess `t`.
+    /// `e` is `E(from - 1)`.
+    fn scan_fwd(&self, words: &[u64], from: i32, e: i32, t: i32) -> Option<i32> {
+        let lookup = &self.ctx.excess_lookup_8;
+        let end = self.len.min((from | 63) + 1);
+
+        let mut e = e;
+        let mut j = from;
+
+        while j < end {
+            if j & 7 == 0 && j + 8 <= end {
+                let byte = ((words[(j >> 6) as usize] >> (j & 63)) & 0xff) as usize;
+
+                if t < e + lookup.min[byte] as i32 || t > e + lookup.max[byte] as i32 {
+                    e += lookup.excess[byte] as i32;
+                    j += 8;
+                    continue;
+                }
+            }
+
+            e += bit_excess(words, j);
+            if e == t {
+                return Some(j);
+            }
+            j += 1;
+        }
+
+        None
+    }
+
+    /// Scan from position `from` back to the start of its block, for the last position with
+    /// excess `t`. `e` is `E(from)`.
+    fn scan_bwd(&self, words: &[u64], from: i32, e: i32, t: i32) -> Option<i32> {
+        let lookup = &self.ctx.excess_lookup_8;
+        let start = from & !63;
+
+        let mut e = e;
+        let mut j = from;
+
+        while j >= start {
+            if (j + 1) & 7 == 0 && j - 7 >= start {
+                let byte = ((words[(j >> 6) as usize] >> ((j - 7) & 63)) & 0xff) as usize;
+
+                // E(j - 8), the excess before this byte.
+                let base = e - lookup.excess[byte] as i32;
+
+                if t < base + lookup.min[byte] as i32 || t > base + lookup.max[byte] as i32 {
+                    e = base;
+                    j -= 8;
+                    continue;
+                }
+            }
+
+            if e == t {
+                return Some(j);
+            }
+
+            e -= bit_excess(words, j);
+            j -= 1;
+        }
+
+        None
+    }
+
+    /// Returns the first block after block `b` that contains excess `t`.
+    fn next_block(&self, b: usize, t: i32) -> Option<usize> {
+        let mut v = self.leaf_start + b;
+
+        loop {
+            if v == 1 {
+                return None;
+            }
+            if v & 1 == 0 && self.contains(v + 1, t) {
+                v += 1;
+                break;
+            }
+            v >>= 1;
+        }
+
+        while v < self.leaf_start {
+            v *= 2;
+            if !self.contains(v, t) {
+                v += 1;
+            }
+        }
+
+        Some(v - self.leaf_start)
+    }
+
+    /// Returns the last block before block `b` that contains excess `t`.
+    fn prev_block(&self, b: usize, t: i32) -> Option<usize> {
+        let mut v = self.leaf_start + b;
+
+        loop {
+            if v == 1 {
+                return None;
+            }
+            if v & 1 == 1 && self.contains(v - 1, t) {
+                v -= 1;
+                break;
+            }
+            v >>= 1;
+        }
+
+        while v < self.leaf_start {
+            v = v * 2 + 1;
+            if !self.contains(v, t) {
+                v -= 1;
+            }
+        }
+
+        Some(v - self.leaf_start)
+    }
+
+    fn contains(&self, v: usize, t: i32) -> bool {
+        self.min[v] <= t && t <= self.max[v]
+    }
+}
+
+/// Returns 1 for an open parenthesis at `i` and -1 for a close parenthesis.
+fn bit_excess(words: &[u64], i: i32) -> i32 {
+    (((words[(i >> 6) as usize] >> (i & 63)) & 1) as i32) * 2 - 1
+}
@@ -0,0 +1,80 @@
+use crate::tree::rmm::RangeMinMax;
+use crate::tree::testing::random_parens;
+
+fn to_words(parens: &str) -> Vec<u64> {
+    let mut words = vec![0; parens.len() / 64 + 1];
+    for (i, c) in parens.bytes().enumerate() {
+        if c == b'(' {
+            words[i >> 6] |= 1 << (i & 63);
+        }
+    }
+    words
+}
+
+#[test]
+fn test_range_min_max_excess() -> anyhow::Result<()> {
+    let parens = "(()(()))((";
+    let words = to_words(parens);
+    let rmm = RangeMinMax::new(&words, parens.len() as i32);
+
+    let want = vec![0, 1, 2, 1, 2, 3, 2, 1, 0, 1, 2];
+    for i in -1..parens.len() as i32 {
+        assert_eq!(
+            want[(i + 1) as usize],
+            rmm.excess(&words, i),
+            "excess({})",
+            i
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_range_min_max_search() -> anyhow::Result<()> {
+    let cases = vec![
+        "".to_string(),
+        "(".to_string(),
+        ")(".to_string(),
+        "((((((((((((((((((((((((((((((((((((((((((((((((((((((((((((((((((((".to_string(),
+        random_parens(7, 300, 9),
+        random_parens(8, 500, 3),
+        // not balanced
+        random_parens(9, 200, 5).replace("((", ")("),
+    ];
+
+    for parens in cases {
+        let words = to_words(&parens);
+        let n = parens.len() as i32;
+        let rmm = RangeMinMax::new(&words, n);
+
+        let excess: Vec<i32> = (-1..n).map(|i| rmm.excess(&words, i)).collect();
+        let e = |i: i32| excess[(i + 1) as usize];
+
+        for i in 0..n {
+            for d in [-3, -2, -1, 1, 2] {
+                let want = (i + 1..n).find(|j| e(*j) == e(i) + d);
+                assert_eq!(
+                    want,
+                    rmm.fwd_search(&words, i, d),
+                    "fwd_search({}, {})",
+                    i,
+                    d
+                );
+            }
+
+            for d in [-3, -2, -1, 0, 1, 2] {
+                let want = (-1..i).rev().find(|j| e(*j) == e(i) + d);
+                assert_eq!(
+                    want,
+                    rmm.bwd_search(&words, i, d),
+                    "bwd_search({}, {})",
+                    i,
+                    d
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,327 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::bitmap::context::Context;
+use crate::int_vec::IntVec;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::CTX;
+
+/// The number of parentheses in a block is `1 << BLOCK_SHIFT`.
+const BLOCK_SHIFT: i32 = 9;
+
+/// The number of blocks in a superblock is `1 << SUPER_SHIFT`.
+const SUPER_SHIFT: usize = 5;
+
+/// A succinct range minimum query index over an array of integers: `2n + 2` bits of balanced
+/// parentheses and a small index of minima. `argmin()` takes constant time and does not need the
+/// array.
+///
+/// The array itself is not kept. It stores the balanced parentheses of the tree in which the
+/// parent of `A[k]` is the nearest `A[p] <= A[k]` with `p < k`, under a virtual root that is less
+/// than every value. The nodes in preorder are the array in order, thus node `k` is the `k + 1`-th
+/// open parenthesis.
+///
+/// With the excess `E(i)`, the count of `(` minus the count of `)` in `[0, i]`, the leftmost min of
+/// `A[l..=r]` is found from the rightmost min excess between the open parentheses of `l` and `r`:
+/// it is the `)` just before the answer, or the answer is `l` if `E` is already min at `l`.
+///
+/// The rightmost min excess in a range is found with:
+/// - blocks of 512 parentheses, each with the offset of its rightmost min excess, 9 bits;
+/// - superblocks of 32 blocks, each block with a sparse table of the min block in the next 2, 4, 8
+///   and 16 blocks of its superblock, 5 bits per level;
+/// - a sparse table over the superblocks with the index of the min block in the next `2^k`
+///   superblocks, 32 bits per level.
+///
+/// Thus the minima take 29 bits per 512 parentheses and `32 * (log(n / 8192) + 1)` bits per
+/// 16384 parentheses, less than 0.1 bit per parenthesis. The parentheses are stored in a
+/// `LabelBitmap` for rank and select.
+///
+/// A query does two select, a constant number of rank, and an excess scan of at most two blocks
+/// with the byte lookup table in `Context`.
+pub struct Rmq {
+    /// The balanced parentheses, `1` is an open parenthesis.
+    pub bitmap: LabelBitmap,
+
+    /// The number of parentheses.
+    pub parens: i32,
+
+    /// The offset in every block of its rightmost min excess.
+    pub block_min: IntVec,
+
+    /// Level `k` from 1 to 4 of the sparse tables in superblocks.
+    /// The element `(k - 1) * blocks + b` is the offset in its superblock of the block with the
+    /// rightmost min excess in blocks `[b, b + 2^k)`, clipped at the end of the superblock.
+    pub local: IntVec,
+
+    /// `top[k][s]` is the index of the block with the rightmost min excess in superblocks
+    /// `[s, s + 2^k)`.
+    pub top: Vec<Vec<i32>>,
+
+    /// The number of values.
+    pub len: i32,
+
+    pub ctx: &'static Context,
+}
+
+impl Rmq {
+    #[allow(dead_code)]
+    pub fn new(values: &[u64]) -> Self {
+        let len = (values.len() + 1) * 2;
+        let mut words = vec![0u64; (len >> 6) + 1];
+
+        // The virtual root.
+        words[0] = 1;
+        let mut i = 1;
+
+        let mut stack: Vec<u64> = vec![];
+        for v in values.iter() {
+            while stack.last().map(|top| *top > *v).unwrap_or(false) {
+                stack.pop();
+                // A close parenthesis is 0 already.
+                i += 1;
+            }
+
+            words[i >> 6] |= 1 << (i & 63);
+            i += 1;
+            stack.push(*v);
+        }
+
+        let mut r = Rmq {
+            bitmap: LabelBitmap::new(words),
+            parens: len as i32,
+            block_min: IntVec::new(BLOCK_SHIFT as u32),
+            local: IntVec::new(SUPER_SHIFT as u32),
+            top: vec![],
+            len: values.len() as i32,
+            ctx: &CTX,
+        };
+
+        let blocks = r.blocks();
+
+        for b in 0..blocks {
+            let start = (b as i32) << BLOCK_SHIFT;
+            let end = r.parens.min(start + (1 << BLOCK_SHIFT));
+            let p = r.scan(start, end - 1);
+            r.block_min.push((p - start) as u64);
+        }
+
+        for k in 1..SUPER_SHIFT {
+            let half = 1 << (k - 1);
+            for b in 0..blocks {
+                let super_end = blocks.min((b | ((1 << SUPER_SHIFT) - 1)) + 1);
+
+                let mut m = r.local_min(k - 1, b);
+                if b + half < super_end {
+                    m = r.pick_block(m, r.local_min(k - 1, b + half));
+                }
+                r.local.push((m & ((1 << SUPER_SHIFT) - 1)) as u64);
+            }
+        }
+
+        let supers = ((blocks - 1) >> SUPER_SHIFT) + 1;
+
+        let level0 = (0..supers)
+            .map(|s| {
+                let first = s << SUPER_SHIFT;
+                let last = blocks.min(first + (1 << SUPER_SHIFT)) - 1;
+                r.local_range_min(first, last) as i32
+            })
+            .collect::<Vec<_>>();
+        r.top.push(level0);
+
+        let mut k = 1;
+        while (1 << k) <= supers {
+            let half = 1 << (k - 1);
+            let prev = &r.top[k - 1];
+
+            let level = (0..supers)
+                .map(|s| {
+                    if s + half < supers {
+                        r.pick_block(prev[s] as usize, prev[s + half] as usize) as i32
+                    } else {
+                        prev[s]
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            r.top.push(level);
+            k += 1;
+        }
+
+        r
+    }
+
+    /// Returns the position of the leftmost min value in `[from, to)`.
+    /// `from` must be less than `to`.
+    #[allow(dead_code)]
+    pub fn argmin(&self, from: i32, to: i32) -> i32 {
+        assert!(
+            0 <= from && from < to && to <= self.len,
+            "invalid range: [{}, {})",
+            from,
+            to
+        );
+
+        // The open parentheses of `A[from]` and `A[to - 1]`.
+        let x = self.bitmap.select_ith_one(from + 1);
+        let y = self.bitmap.select_ith_one(to);
+
+        let q = self.min_excess_pos(x, y);
+
+        if self.excess(x) <= self.excess(q) {
+            // Every other node in the range is in the subtree of `from`.
+            return from;
+        }
+
+        // The rightmost min excess is the close parenthesis just before the answer.
+        self.bitmap.count_ones(q + 1).0 - 1
+    }
+
+    /// Returns the position of the rightmost min excess in `[x, y]`.
+    fn min_excess_pos(&self, x: i32, y: i32) -> i32 {
+        let bx = (x >> BLOCK_SHIFT) as usize;
+        let by = (y >> BLOCK_SHIFT) as usize;
+
+        if bx == by {
+            return self.scan(x, y);
+        }
+
+        let mut p = self.scan(x, (((bx + 1) as i32) << BLOCK_SHIFT) - 1);
+
+        if bx + 1 < by {
+            let b = self.block_range_min(bx + 1, by - 1);
+            p = self.pick(p, self.block_min_pos(b));
+        }
+
+        self.pick(p, self.scan((by as i32) << BLOCK_SHIFT, y))
+    }
+
+    /// Returns the block with the rightmost min excess in blocks `[first, last]`.
+    fn block_range_min(&self, first: usize, last: usize) -> usize {
+        let sf = first >> SUPER_SHIFT;
+        let sl = last >> SUPER_SHIFT;
+
+        if sf == sl {
+            return self.local_range_min(first, last);
+        }
+
+        let mut m = self.local_range_min(first, ((sf + 1) << SUPER_SHIFT) - 1);
+
+        if sf + 1 < sl {
+            let k = log2(sl - sf - 1);
+            let top = &self.top[k];
+            let t = self.pick_block(top[sf + 1] as usize, top[sl - (1 << k)] as usize);
+            m = self.pick_block(m, t);
+        }
+
+        self.pick_block(m, self.local_range_min(sl << SUPER_SHIFT, last))
+    }
+
+    /// Returns the block with the rightmost min excess in blocks `[first, last]` of a superblock.
+    fn local_range_min(&self, first: usize, last: usize) -> usize {
+        // Two overlapping ranges of 16 blocks cover a superblock.
+        let k = log2(last - first + 1).min(SUPER_SHIFT - 1);
+
+        let a = self.local_min(k, first);
+        let b = self.local_min(k, last + 1 - (1 << k));
+        self.pick_block(a, b)
+    }
+
+    /// Returns the block with the rightmost min excess in blocks `[b, b + 2^k)`, clipped at the end
+    /// of the superblock.
+    fn local_min(&self, k: usize, b: usize) -> usize {
+        if k == 0 {
+            return b;
+        }
+
+        let base = b >> SUPER_SHIFT << SUPER_SHIFT;
+        base + self.local.get((k - 1) * self.blocks() + b) as usize
+    }
+
+    /// Returns the position of the rightmost min excess in block `b`.
+    fn block_min_pos(&self, b: usize) -> i32 {
+        ((b as i32) << BLOCK_SHIFT) + self.block_min.get(b) as i32
+    }
+
+    /// Returns the one of blocks `a <= b` with the rightmost min excess.
+    fn pick_block(&self, a: usize, b: usize) -> usize {
+        if a == b || self.excess(self.block_min_pos(b)) <= self.excess(self.block_min_pos(a)) {
+            b
+        } else {
+            a
+        }
+    }
+
+    /// Returns the one of positions `a <= b` with the rightmost min excess.
+    fn pick(&self, a: i32, b: i32) -> i32 {
+        if self.excess(b) <= self.excess(a) {
+            b
+        } else {
+            a
+        }
+    }
+
+    /// Returns the position of the rightmost min excess in `[x, y]` by scanning the parentheses,
+    /// a byte at a time where possible.
+    fn scan(&self, x: i32, y: i32) -> i32 {
+        let words = &self.bitmap.words;
+        let lookup = &self.ctx.excess_lookup_8;
+
+        // The excess relative to `E(x - 1)`.
+        let mut e = 0;
+        let mut min = i32::MAX;
+        let mut min_pos = x;
+
+        // The start of the byte that contains the min and the excess before it.
+        let mut min_byte = None;
+
+        let mut i = x;
+        while i <= y {
+            if i & 7 == 0 && i + 7 <= y {
+                let b = (words[(i >> 6) as usize] >> (i & 63)) as u8 as usize;
+
+                if e + lookup.min[b] as i32 <= min {
+                    min = e + lookup.min[b] as i32;
+                    min_byte = Some((i, e));
+                }
+
+                e += lookup.excess[b] as i32;
+                i += 8;
+            } else {
+                e += self.bitmap.get_bit(i) * 2 - 1;
+
+                if e <= min {
+                    min = e;
+                    min_pos = i;
+                    min_byte = None;
+                }
+
+                i += 1;
+            }
+        }
+
+        if let Some((start, mut e)) = min_byte {
+            for j in start..start + 8 {
+                e += self.bitmap.get_bit(j) * 2 - 1;
+                if e == min {
+                    min_pos = j;
+                }
+            }
+        }
+
+        min_pos
+    }
+
+    /// Returns `E(i)`.
+    fn excess(&self, i: i32) -> i32 {
+        self.bitmap.count_ones(i + 1).0 * 2 - (i + 1)
+    }
+
+    fn blocks(&self) -> usize {
+        (((self.parens - 1) >> BLOCK_SHIFT) + 1) as usize
+    }
+}
+
+/// Returns `floor(log2(n))` of a positive `n`.
+fn log2(n: usize) -> usize {
+    (usize::BITS - 1 - n.leading_zeros()) as usize
+}
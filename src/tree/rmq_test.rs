@@ -0,0 +1,97 @@
+use crate::testing::lcg;
+use crate::tree::rmq::Rmq;
+
+#[test]
+fn test_rmq_example() -> anyhow::Result<()> {
+    //            0  1  2  3  4  5  6  7
+    let values = [3, 1, 4, 1, 5, 9, 2, 6];
+    let r = Rmq::new(&values);
+
+    assert_eq!(1, r.argmin(0, 8));
+    assert_eq!(0, r.argmin(0, 1));
+    assert_eq!(3, r.argmin(2, 8));
+    assert_eq!(6, r.argmin(4, 8));
+    assert_eq!(4, r.argmin(4, 6));
+    assert_eq!(2, r.argmin(2, 3));
+
+    Ok(())
+}
+
+#[test]
+fn test_rmq() -> anyhow::Result<()> {
+    let mut rand = lcg(7);
+
+    for (n, m) in [(1, 1), (10, 3), (100, 1000), (300, 5), (500, 2)] {
+        let values = (0..n).map(|_| rand(m)).collect::<Vec<_>>();
+        let r = Rmq::new(&values);
+
+        for from in 0..n {
+            for to in (from + 1..=n).step_by(3) {
+                let want = (from..to).min_by_key(|i| values[*i]).unwrap();
+                assert_eq!(
+                    want as i32,
+                    r.argmin(from as i32, to as i32),
+                    "argmin({}, {})",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_rmq_blocks() -> anyhow::Result<()> {
+    let mut rand = lcg(11);
+
+    // Spans several superblocks of 16384 parentheses.
+    let n = 20000;
+    let cases: Vec<Vec<u64>> = vec![
+        (0..n).map(|_| rand(1000)).collect(),
+        (0..n).map(|_| rand(3)).collect(),
+        (0..n).map(|i| i as u64).collect(),
+        (0..n).map(|i| (n - i) as u64).collect(),
+        (0..n).map(|_| 5).collect(),
+        // long increasing runs followed by a drop
+        (0..n).map(|i| (i % 3000) as u64).collect(),
+    ];
+
+    for values in cases {
+        let r = Rmq::new(&values);
+
+        // Every `to` from a few `from`, to cover every combination of blocks.
+        for from in (0..n).step_by(4999) {
+            let mut want = from;
+            for to in from + 1..=n {
+                if values[to - 1] < values[want] {
+                    want = to - 1;
+                }
+                assert_eq!(
+                    want as i32,
+                    r.argmin(from as i32, to as i32),
+                    "argmin({}, {})",
+                    from,
+                    to
+                );
+            }
+        }
+
+        for _ in 0..300 {
+            let from = rand(n as u64) as usize;
+            let to = from + 1 + rand((n - from) as u64) as usize;
+
+            let want = (from..to).min_by_key(|i| values[*i]).unwrap();
+            assert_eq!(
+                want as i32,
+                r.argmin(from as i32, to as i32),
+                "argmin({}, {})",
+                from,
+                to
+            );
+        }
+    }
+
+    Ok(())
+}
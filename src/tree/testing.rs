@@ -0,0 +1,79 @@
+use crate::testing::lcg;
+
+/// Generate trees in balanced parentheses for tests.
+pub fn random_parens(seed: u64, nodes: usize, deep: u64) -> String {
+    let mut rand = lcg(seed);
+
+    let mut s = String::from("(");
+    let mut open = 1;
+    let mut created = 1;
+
+    while created < nodes {
+        // go deeper with probability deep/10, if there is still an open node above the root.
+        if open > 1 && rand(10) >= deep {
+            s.push(')');
+            open -= 1;
+        } else {
+            s.push('(');
+            open += 1;
+            created += 1;
+        }
+    }
+
+    for _ in 0..open {
+        s.push(')');
+    }
+
+    s
+}
+
+/// A naive tree built from balanced parentheses to verify the answers of succinct trees.
+pub struct NaiveTree {
+    pub parens: Vec<u8>,
+    pub close: Vec<i32>,
+    pub open: Vec<i32>,
+    pub parent: Vec<i32>,
+    pub depth: Vec<i32>,
+    pub children: Vec<Vec<i32>>,
+}
+
+impl NaiveTree {
+    pub fn new(parens: &str) -> Self {
+        let parens = parens.as_bytes().to_vec();
+        let n = parens.len();
+
+        let mut t = NaiveTree {
+            parens,
+            close: vec![-1; n],
+            open: vec![-1; n],
+            parent: vec![-1; n],
+            depth: vec![-1; n],
+            children: vec![vec![]; n],
+        };
+
+        let mut stack: Vec<usize> = vec![];
+        for i in 0..n {
+            if t.parens[i] == b'(' {
+                t.parent[i] = stack.last().map(|x| *x as i32).unwrap_or(-1);
+                if let Some(p) = stack.last() {
+                    t.children[*p].push(i as i32);
+                }
+                t.depth[i] = stack.len() as i32;
+                stack.push(i);
+            } else {
+                let o = stack.pop().unwrap();
+                t.close[o] = i as i32;
+                t.open[i] = o as i32;
+            }
+        }
+
+        t
+    }
+
+    /// Returns the positions of all nodes, in preorder.
+    pub fn nodes(&self) -> Vec<i32> {
+        (0..self.parens.len() as i32)
+            .filter(|i| self.parens[*i as usize] == b'(')
+            .collect()
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::BinaryHeap;
+
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+
+/// A wavelet matrix over a sequence of integers of up to 64 bits.
+///
+/// `levels[l]` stores the l-th most significant bit(of `bits` bits) of every value.
+/// Between two levels the values are stably partitioned by the bit of the upper level: all values
+/// with `0` go first, followed by all values with `1`.
+///
+/// Thus a position `i` at level `l` maps to the next level as:
+/// - `count_zeros(i)`, if the bit at `i` is `0`;
+/// - `zeros[l] + count_ones(i)`, if the bit at `i` is `1`.
+pub struct WaveletMatrix<BM>
+where BM: BitmapOps
+{
+    pub levels: Vec<BM>,
+
+    /// The count of `0` at every level.
+    pub zeros: Vec<i32>,
+
+    /// The number of bits of a value.
+    pub bits: u32,
+
+    /// The number of values.
+    pub len: i32,
+}
+
+impl<BM> WaveletMatrix<BM>
+where BM: BuildBitmap
+{
+    /// Build with the least number of bits that can represent every value.
+    #[allow(dead_code)]
+    pub fn new(values: &[u64]) -> Self {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let bits = 64 - max.leading_zeros();
+
+        let mut levels = Vec::with_capacity(bits as usize);
+        let mut zeros = Vec::with_capacity(bits as usize);
+
+        let mut cur = values.to_vec();
+        let mut next = Vec::with_capacity(values.len());
+
+        for l in 0..bits {
+            let shift = bits - 1 - l;
+
+            // One more word to make `count_ones(len)` valid.
+            let mut words = vec![0u64; (values.len() >> 6) + 1];
+
+            for (i, v) in cur.iter().enumerate() {
+                words[i >> 6] |= ((*v >> shift) & 1) << (i & 63);
+            }
+
+            next.clear();
+            next.extend(cur.iter().filter(|v| (*v >> shift) & 1 == 0));
+            zeros.push(next.len() as i32);
+            next.extend(cur.iter().filter(|v| (*v >> shift) & 1 == 1));
+
+            std::mem::swap(&mut cur, &mut next);
+
+            levels.push(BM::new(words));
+        }
+
+        WaveletMatrix {
+            levels,
+            zeros,
+            bits,
+            len: values.len() as i32,
+        }
+    }
+}
+
+/// A range of a level, that contains values with the same upper bits, used by `top_k()`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Range {
+    width: i32,
+    level: u32,
+    prefix: u64,
+    from: i32,
+    to: i32,
+}
+
+impl<BM> WaveletMatrix<BM>
+where BM: BitmapOps
+{
+    /// Returns the value at position `i`.
+    #[allow(dead_code)]
+    pub fn access(&self, i: i32) -> u64 {
+        let mut i = i;
+        let mut v = 0;
+
+        for (l, bm) in self.levels.iter().enumerate() {
+            let bit = bm.get_bit(i);
+            i = self.map_down(l, i, bit);
+            v = (v << 1) | bit as u64;
+        }
+
+        v
+    }
+
+    /// Returns the count of value `v` in `[0, i)`.
+    #[allow(dead_code)]
+    pub fn rank(&self, v: u64, i: i32) -> i32 {
+        if !self.fits(v) {
+            return 0;
+        }
+
+        let (start, end) = self.descend(v, 0, i);
+        end - start
+    }
+
+    /// Returns the position of the k-th(0-based) value `v`, or `None` if there are not that many
+    /// `v`.
+    #[allow(dead_code)]
+    pub fn select(&self, v: u64, k: i32) -> Option<i32> {
+        if !self.fits(v) {
+            return None;
+        }
+
+        let (start, end) = self.descend(v, 0, self.len);
+        if k < 0 || k >= end - start {
+            return None;
+        }
+
+        // Walk up from the bottom.
+        let mut pos = start + k;
+
+        for l in (0..self.levels.len()).rev() {
+            let bm = &self.levels[l];
+            pos = if self.bit(v, l) == 0 {
+                bm.select_ith_zero(pos)
+            } else {
+                bm.select_ith_one(pos - self.zeros[l])
+            };
+        }
+
+        Some(pos)
+    }
+
+    /// Returns the count of values in `[lo, hi)` at positions in `[from, to)`.
+    #[allow(dead_code)]
+    pub fn range_freq(&self, from: i32, to: i32, lo: u64, hi: u64) -> i32 {
+        if lo >= hi {
+            return 0;
+        }
+        self.count_less(from, to, hi) - self.count_less(from, to, lo)
+    }
+
+    /// Returns the k-th(0-based) smallest value at positions in `[from, to)`.
+    /// `k` must be less than `to - from`.
+    #[allow(dead_code)]
+    pub fn quantile(&self, from: i32, to: i32, k: i32) -> u64 {
+        let (mut from, mut to, mut k) = (from, to, k);
+        let mut v = 0;
+
+        for (l, bm) in self.levels.iter().enumerate() {
+            let zeros = bm.count_zeros(to).0 - bm.count_zeros(from).0;
+
+            let bit = if k < zeros {
+                0
+            } else {
+                k -= zeros;
+                1
+            };
+
+            from = self.map_down(l, from, bit);
+            to = self.map_down(l, to, bit);
+            v = (v << 1) | bit as u64;
+        }
+
+        v
+    }
+
+    /// Returns the `k` most frequent values at positions in `[from, to)`, along with their count,
+    /// the most frequent first.
+    ///
+    /// It does a best-first search: a range covering more positions is expanded first.
+    #[allow(dead_code)]
+    pub fn top_k(&self, from: i32, to: i32, k: usize) -> Vec<(u64, i32)> {
+        let mut res = Vec::with_capacity(k);
+
+        let mut heap = BinaryHeap::new();
+        if from < to {
+            heap.push(Range {
+                width: to - from,
+                level: 0,
+                prefix: 0,
+                from,
+                to,
+            });
+        }
+
+        while res.len() < k {
+            let r = match heap.pop() {
+                None => break,
+                Some(r) => r,
+            };
+
+            if r.level == self.bits {
+                res.push((r.prefix, r.width));
+                continue;
+            }
+
+            let l = r.level as usize;
+            for bit in 0..2 {
+                let from = self.map_down(l, r.from, bit);
+                let to = self.map_down(l, r.to, bit);
+
+                if from < to {
+                    heap.push(Range {
+                        width: to - from,
+                        level: r.level + 1,
+                        prefix: (r.prefix << 1) | bit as u64,
+                        from,
+                        to,
+                    });
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Returns the count of values less than `v` at positions in `[from, to)`.
+    fn count_less(&self, from: i32, to: i32, v: u64) -> i32 {
+        if !self.fits(v) {
+            return to - from;
+        }
+
+        let (mut from, mut to) = (from, to);
+        let mut cnt = 0;
+
+        for (l, bm) in self.levels.iter().enumerate() {
+            let bit = self.bit(v, l);
+
+            if bit == 1 {
+                // values with `0` at this level are less than v
+                cnt += bm.count_zeros(to).0 - bm.count_zeros(from).0;
+            }
+
+            from = self.map_down(l, from, bit);
+            to = self.map_down(l, to, bit);
+        }
+
+        cnt
+    }
+
+    /// Map the range `[from, to)` at the top level to the range of value `v` at the bottom.
+    fn descend(&self, v: u64, from: i32, to: i32) -> (i32, i32) {
+        let (mut from, mut to) = (from, to);
+
+        for l in 0..self.levels.len() {
+            let bit = self.bit(v, l);
+            from = self.map_down(l, from, bit);
+            to = self.map_down(l, to, bit);
+        }
+
+        (from, to)
+    }
+
+    /// Map position `i` at level `l` to the next level, following the values with `bit` at `l`.
+    fn map_down(&self, l: usize, i: i32, bit: i32) -> i32 {
+        let bm = &self.levels[l];
+        if bit == 0 {
+            bm.count_zeros(i).0
+        } else {
+            self.zeros[l] + bm.count_ones(i).0
+        }
+    }
+
+    /// Returns the bit of `v` at level `l`.
+    fn bit(&self, v: u64, l: usize) -> i32 {
+        ((v >> (self.bits as usize - 1 - l)) & 1) as i32
+    }
+
+    /// Returns if `v` can be represented with `bits` bits.
+    fn fits(&self, v: u64) -> bool {
+        self.bits == 64 || v >> self.bits == 0
+    }
+}
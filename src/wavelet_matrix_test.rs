@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use crate::static_kv::LabelBitmap;
+use crate::testing::lcg;
+use crate::wavelet_matrix::WaveletMatrix;
+
+fn sequences() -> Vec<Vec<u64>> {
+    let mut rand = lcg(1);
+    let mut random = |n: usize, modulo: u64| -> Vec<u64> {
+        // 62 random bits from two draws, for a large modulo.
+        (0..n)
+            .map(|_| (rand(1 << 31) << 31 | rand(1 << 31)) % modulo)
+            .collect()
+    };
+
+    vec![
+        vec![],
+        vec![0, 0, 0],
+        vec![5],
+        vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5],
+        random(200, 10),
+        random(150, 1 << 40),
+        vec![u64::MAX, 0, u64::MAX - 1, 1 << 63, 7, u64::MAX],
+    ]
+}
+
+#[test]
+fn test_wavelet_matrix_access_rank_select() -> anyhow::Result<()> {
+    for seq in sequences() {
+        let wm = WaveletMatrix::<LabelBitmap>::new(&seq);
+        let n = seq.len() as i32;
+
+        for i in 0..n {
+            assert_eq!(seq[i as usize], wm.access(i), "access({})", i);
+        }
+
+        let mut values = seq.clone();
+        values.extend_from_slice(&[0, 2, 1 << 41, u64::MAX]);
+        values.sort_unstable();
+        values.dedup();
+
+        for v in values {
+            let mut cnt = 0;
+            for i in 0..=n {
+                assert_eq!(cnt, wm.rank(v, i), "rank({}, {})", v, i);
+                if i < n && seq[i as usize] == v {
+                    assert_eq!(Some(i), wm.select(v, cnt), "select({}, {})", v, cnt);
+                    cnt += 1;
+                }
+            }
+            assert_eq!(None, wm.select(v, cnt));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_wavelet_matrix_range_queries() -> anyhow::Result<()> {
+    for seq in sequences() {
+        let wm = WaveletMatrix::<LabelBitmap>::new(&seq);
+        let n = seq.len() as i32;
+
+        let mut bounds = seq.clone();
+        bounds.extend_from_slice(&[0, 3, 6, 1 << 39, u64::MAX]);
+
+        for from in (0..n).step_by(29) {
+            for to in (from..=n).step_by(23) {
+                let sub = &seq[from as usize..to as usize];
+
+                // quantile
+                let mut sorted = sub.to_vec();
+                sorted.sort_unstable();
+                for (k, want) in sorted.iter().enumerate() {
+                    assert_eq!(*want, wm.quantile(from, to, k as i32));
+                }
+
+                // range_freq
+                for lo in bounds.iter().step_by(17) {
+                    for hi in bounds.iter().step_by(7) {
+                        let want = sub.iter().filter(|x| *x >= lo && *x < hi).count() as i32;
+                        assert_eq!(
+                            want,
+                            wm.range_freq(from, to, *lo, *hi),
+                            "range_freq({}, {}, {}, {})",
+                            from,
+                            to,
+                            lo,
+                            hi
+                        );
+                    }
+                }
+
+                // top_k
+                let mut freq = BTreeMap::new();
+                for x in sub {
+                    *freq.entry(*x).or_insert(0) += 1;
+                }
+
+                for k in [0, 1, 3, 100] {
+                    let got = wm.top_k(from, to, k);
+                    assert_eq!(k.min(freq.len()), got.len());
+
+                    let mut counts: Vec<i32> = freq.values().copied().collect();
+                    counts.sort_unstable_by(|a, b| b.cmp(a));
+
+                    for (i, (v, cnt)) in got.iter().enumerate() {
+                        assert_eq!(counts[i], *cnt);
+                        assert_eq!(Some(cnt), freq.get(v));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+
+/// A wavelet tree over a sequence of bytes.
+///
+/// It is stored level by level: `levels[l]` stores the l-th most significant bit of every symbol.
+/// In `levels[l]`, the symbols are ordered by their upper `l` bits, so that every tree node at
+/// level `l` is a continuous range.
+///
+/// E.g.: the children of a node in range `[s, e)` at level `l` are:
+/// the node with `0` in range `[s, s + zeros)` and the node with `1` in range `[s + zeros, e)` at
+/// level `l + 1`, where `zeros` is the count of `0` in `[s, e)` at level `l`.
+pub struct WaveletTree<BM>
+where BM: BitmapOps
+{
+    pub levels: Vec<BM>,
+
+    /// The number of symbols.
+    pub len: i32,
+}
+
+/// The number of bits of a symbol.
+const LEVELS: usize = 8;
+
+impl<BM> WaveletTree<BM>
+where BM: BuildBitmap
+{
+    #[allow(dead_code)]
+    pub fn new(seq: &[u8]) -> Self {
+        let mut levels = Vec::with_capacity(LEVELS);
+
+        let mut cur = seq.to_vec();
+
+        for l in 0..LEVELS {
+            let shift = LEVELS - 1 - l;
+
+            // One more word to make `count_ones(len)` valid.
+            let mut words = vec![0u64; (seq.len() >> 6) + 1];
+
+            for (i, c) in cur.iter().enumerate() {
+                words[i >> 6] |= ((*c as u64 >> shift) & 1) << (i & 63);
+            }
+
+            levels.push(BM::new(words));
+
+            // A stable sort by the upper l+1 bits splits every node into its two children.
+            cur.sort_by_key(|c| *c >> shift);
+        }
+
+        WaveletTree {
+            levels,
+            len: seq.len() as i32,
+        }
+    }
+}
+
+/// A node of the tree, i.e., a range `[start, end)` on a level.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    start: i32,
+    end: i32,
+
+    /// The count of `0` before `start`.
+    zeros_start: i32,
+
+    /// The count of `0` before `end`.
+    zeros_end: i32,
+}
+
+impl Node {
+    fn new<BM: BitmapOps>(bm: &BM, start: i32, end: i32) -> Self {
+        Node {
+            start,
+            end,
+            zeros_start: bm.count_zeros(start).0,
+            zeros_end: bm.count_zeros(end).0,
+        }
+    }
+
+    /// Returns the range of the child node at the next level.
+    fn child(&self, bit: i32) -> (i32, i32) {
+        let mid = self.start + self.zeros_end - self.zeros_start;
+        if bit == 0 {
+            (self.start, mid)
+        } else {
+            (mid, self.end)
+        }
+    }
+
+    /// Map a position `i` in this node to the position in the child node at the next level.
+    /// `zeros_i` is the count of `0` before `i`.
+    fn map_down(&self, i: i32, zeros_i: i32, bit: i32) -> i32 {
+        let (child_start, _) = self.child(bit);
+        if bit == 0 {
+            child_start + zeros_i - self.zeros_start
+        } else {
+            child_start + (i - self.start) - (zeros_i - self.zeros_start)
+        }
+    }
+}
+
+impl<BM> WaveletTree<BM>
+where BM: BitmapOps
+{
+    /// Returns the symbol at position `i`.
+    #[allow(dead_code)]
+    pub fn access(&self, i: i32) -> u8 {
+        let mut i = i;
+        let (mut start, mut end) = (0, self.len);
+        let mut c = 0;
+
+        for bm in self.levels.iter() {
+            let node = Node::new(bm, start, end);
+            let (zeros_i, bit) = bm.count_zeros(i);
+
+            i = node.map_down(i, zeros_i, bit);
+            let (s, e) = node.child(bit);
+            start = s;
+            end = e;
+
+            c = (c << 1) | bit as u8;
+        }
+
+        c
+    }
+
+    /// Returns the count of symbol `c` in `[0, i)`.
+    #[allow(dead_code)]
+    pub fn rank(&self, c: u8, i: i32) -> i32 {
+        let mut i = i;
+        let (mut start, mut end) = (0, self.len);
+
+        for (l, bm) in self.levels.iter().enumerate() {
+            let bit = ((c >> (LEVELS - 1 - l)) & 1) as i32;
+            let node = Node::new(bm, start, end);
+
+            i = node.map_down(i, bm.count_zeros(i).0, bit);
+            let (s, e) = node.child(bit);
+            start = s;
+            end = e;
+        }
+
+        i - start
+    }
+
+    /// Returns the position of the k-th(0-based) symbol `c`, or `None` if there are not that many
+    /// `c`.
+    #[allow(dead_code)]
+    pub fn select(&self, c: u8, k: i32) -> Option<i32> {
+        let mut starts = [0; LEVELS + 1];
+        let (mut start, mut end) = (0, self.len);
+
+        for (l, bm) in self.levels.iter().enumerate() {
+            let bit = ((c >> (LEVELS - 1 - l)) & 1) as i32;
+            let (s, e) = Node::new(bm, start, end).child(bit);
+            start = s;
+            end = e;
+            starts[l + 1] = start;
+        }
+
+        if k < 0 || k >= end - start {
+            return None;
+        }
+
+        // Walk up from the leaf.
+        let mut pos = start + k;
+
+        for l in (0..LEVELS).rev() {
+            let bm = &self.levels[l];
+            let bit = (c >> (LEVELS - 1 - l)) & 1;
+            let offset = pos - starts[l + 1];
+
+            pos = if bit == 0 {
+                bm.select_ith_zero(bm.count_zeros(starts[l]).0 + offset)
+            } else {
+                bm.select_ith_one(bm.count_ones(starts[l]).0 + offset)
+            };
+        }
+
+        Some(pos)
+    }
+
+    /// Returns the k-th(0-based) smallest symbol in range `[from, to)`.
+    /// `k` must be less than `to - from`.
+    #[allow(dead_code)]
+    pub fn quantile(&self, from: i32, to: i32, k: i32) -> u8 {
+        let (mut from, mut to, mut k) = (from, to, k);
+        let (mut start, mut end) = (0, self.len);
+        let mut c = 0;
+
+        for bm in self.levels.iter() {
+            let node = Node::new(bm, start, end);
+            let zeros_from = bm.count_zeros(from).0;
+            let zeros_to = bm.count_zeros(to).0;
+
+            let zeros = zeros_to - zeros_from;
+            let bit = if k < zeros {
+                0
+            } else {
+                k -= zeros;
+                1
+            };
+
+            from = node.map_down(from, zeros_from, bit);
+            to = node.map_down(to, zeros_to, bit);
+            let (s, e) = node.child(bit);
+            start = s;
+            end = e;
+
+            c = (c << 1) | bit as u8;
+        }
+
+        c
+    }
+}
@@ -0,0 +1,125 @@
+use crate::bitmap::rle_bitmap::RleBitmap;
+use crate::static_kv::LabelBitmap;
+use crate::static_kv::StaticKV;
+use crate::wavelet_tree::WaveletTree;
+
+fn sequences() -> Vec<Vec<u8>> {
+    // a simple LCG to generate data without external dependency.
+    let mut x: u32 = 1;
+    let mut random = |n: usize, modulo: u32| -> Vec<u8> {
+        (0..n)
+            .map(|_| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345);
+                ((x >> 16) % modulo) as u8
+            })
+            .collect()
+    };
+
+    vec![
+        vec![],
+        vec![0],
+        vec![255],
+        b"abracadabra".to_vec(),
+        random(100, 4),
+        random(300, 256),
+        vec![7; 130],
+    ]
+}
+
+fn check_wavelet_tree<F>(build: F)
+where F: Fn(&[u8]) -> Box<dyn Fn(&str, u8, i32, i32) -> i32> {
+    for seq in sequences() {
+        let wt = build(&seq);
+        let n = seq.len() as i32;
+
+        for i in 0..n {
+            assert_eq!(
+                seq[i as usize] as i32,
+                wt("access", 0, i, 0),
+                "access({})",
+                i
+            );
+        }
+
+        let mut symbols = seq.clone();
+        symbols.extend_from_slice(&[0, 1, 97, 255]);
+        symbols.sort_unstable();
+        symbols.dedup();
+
+        for c in symbols.iter().copied() {
+            let mut cnt = 0;
+            for i in 0..=n {
+                assert_eq!(cnt, wt("rank", c, i, 0), "rank({}, {})", c, i);
+                if i < n && seq[i as usize] == c {
+                    assert_eq!(i, wt("select", c, cnt, 0), "select({}, {})", c, cnt);
+                    cnt += 1;
+                }
+            }
+            assert_eq!(-1, wt("select", c, cnt, 0));
+        }
+
+        for from in (0..n).step_by(17) {
+            for to in (from + 1..=n).step_by(13) {
+                let mut sorted = seq[from as usize..to as usize].to_vec();
+                sorted.sort_unstable();
+                for k in 0..(to - from) {
+                    assert_eq!(
+                        sorted[k as usize] as i32,
+                        wt("quantile", 0, from, to * 1000 + k),
+                        "quantile({}, {}, {})",
+                        from,
+                        to,
+                        k
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Wrap the API in a closure to test trees with different bitmap types.
+macro_rules! api {
+    ($bm:ty) => {
+        |seq: &[u8]| -> Box<dyn Fn(&str, u8, i32, i32) -> i32> {
+            let wt = WaveletTree::<$bm>::new(seq);
+            Box::new(move |op, c, a, b| match op {
+                "access" => wt.access(a) as i32,
+                "rank" => wt.rank(c, a),
+                "select" => wt.select(c, a).unwrap_or(-1),
+                "quantile" => wt.quantile(a, b / 1000, b % 1000) as i32,
+                _ => unreachable!(),
+            })
+        }
+    };
+}
+
+#[test]
+fn test_wavelet_tree() -> anyhow::Result<()> {
+    check_wavelet_tree(api!(LabelBitmap));
+    Ok(())
+}
+
+#[test]
+fn test_wavelet_tree_rle_bitmap() -> anyhow::Result<()> {
+    check_wavelet_tree(api!(RleBitmap));
+    Ok(())
+}
+
+#[test]
+fn test_wavelet_tree_static_kv_labels() -> anyhow::Result<()> {
+    let keys = ["abc", "abcd", "abd", "abde", "bc", "bcd", "bcde", "cde"];
+    let keys: Vec<Vec<u8>> = keys.iter().map(|x| x.as_bytes().to_vec()).collect();
+
+    let kv = StaticKV::<LabelBitmap>::new(&keys);
+    let wt = WaveletTree::<LabelBitmap>::new(&kv.labels);
+
+    assert_eq!(b"abcbcdcddedee".to_vec(), kv.labels);
+
+    assert_eq!(b'c', wt.access(2));
+    assert_eq!(4, wt.rank(b'd', 13));
+    assert_eq!(Some(12), wt.select(b'e', 2));
+    assert_eq!(b'a', wt.quantile(0, 13, 0));
+    assert_eq!(b'e', wt.quantile(0, 13, 12));
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::bitmap::bitmap_ops::BitmapOps;
+use crate::bitmap::bitmap_ops::BuildBitmap;
+use crate::int_vec::IntVec;
+use crate::static_kv::get_bit;
+use crate::static_kv::StaticKV;
+
+/// A `StaticKV` with a weight for every key, for ranked completion.
+///
+/// Both are packed arrays indexed by node id:
+/// - `key_weight[v]` is the weight of the key ending at `v`, or 0 if `v` is not a key;
+/// - `max_weight[v]` is the max weight of the keys in the subtree of `v`.
+pub struct WeightedKV<LBM>
+where LBM: BitmapOps
+{
+    pub kv: StaticKV<LBM>,
+    pub key_weight: IntVec,
+    pub max_weight: IntVec,
+}
+
+/// An entry in the best-first search of `top_k()`: either a key or a subtree to expand.
+///
+/// Entries are popped by weight, then by path, the smaller first. A key goes before the subtree
+/// with the same path, so that keys of the same weight are returned in sorted order.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Entry {
+    weight: u64,
+    path: Reverse<Vec<u8>>,
+    is_key: bool,
+    node_id: i32,
+}
+
+impl<LBM> WeightedKV<LBM>
+where LBM: BuildBitmap
+{
+    /// Build from a sorted list of keys and the weight of every key.
+    #[allow(dead_code)]
+    pub fn new(keys: &[Vec<u8>], weights: &[u64]) -> Self {
+        assert_eq!(keys.len(), weights.len(), "every key must have a weight");
+
+        let kv = StaticKV::<LBM>::new(keys);
+        let nodes = kv.labels.len() + 1;
+
+        let mut key_weight = vec![0u64; nodes];
+        for (k, w) in keys.iter().zip(weights.iter()) {
+            let v = kv.find_node(k).unwrap() as usize;
+            key_weight[v] = key_weight[v].max(*w);
+        }
+
+        // A child always has a greater node id than its parent.
+        let mut max_weight = key_weight.clone();
+        for v in (1..nodes).rev() {
+            let parent = kv
+                .label_bitmap
+                .count_ones(kv.label_bitmap.select_ith_zero(v as i32 - 1))
+                .0 as usize;
+            max_weight[parent] = max_weight[parent].max(max_weight[v]);
+        }
+
+        WeightedKV {
+            kv,
+            key_weight: IntVec::from_values(&key_weight),
+            max_weight: IntVec::from_values(&max_weight),
+        }
+    }
+}
+
+impl<LBM> WeightedKV<LBM>
+where LBM: BitmapOps
+{
+    /// Returns the weight of `key`, or `None` if it is not a key.
+    #[allow(dead_code)]
+    pub fn weight(&self, key: &[u8]) -> Option<u64> {
+        let v = self.kv.find_node(key)?;
+        if get_bit(&self.kv.leaves, v as usize) != 0 {
+            Some(self.key_weight.get(v as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the `k` keys starting with `prefix` of the highest weights, along with the weights.
+    /// Keys of the same weight are in sorted order.
+    ///
+    /// It does a best-first search: the subtree with the greatest max weight is expanded first.
+    #[allow(dead_code)]
+    pub fn top_k(&self, prefix: &[u8], k: usize) -> Vec<(Vec<u8>, u64)> {
+        let mut res = Vec::with_capacity(k);
+
+        let root = match self.kv.find_node(prefix) {
+            None => return res,
+            Some(v) => v,
+        };
+
+        let mut heap = BinaryHeap::new();
+        heap.push(self.subtree(root, prefix.to_vec()));
+
+        while res.len() < k {
+            let e = match heap.pop() {
+                None => break,
+                Some(e) => e,
+            };
+
+            if e.is_key {
+                res.push((e.path.0, e.weight));
+                continue;
+            }
+
+            let v = e.node_id;
+            let path = e.path.0;
+
+            if get_bit(&self.kv.leaves, v as usize) != 0 {
+                heap.push(Entry {
+                    weight: self.key_weight.get(v as usize),
+                    path: Reverse(path.clone()),
+                    is_key: true,
+                    node_id: v,
+                });
+            }
+
+            for (label, child) in self.kv.children(v) {
+                let mut child_path = path.clone();
+                child_path.push(label);
+                heap.push(self.subtree(child, child_path));
+            }
+        }
+
+        res
+    }
+
+    fn subtree(&self, node_id: i32, path: Vec<u8>) -> Entry {
+        Entry {
+            weight: self.max_weight.get(node_id as usize),
+            path: Reverse(path),
+            is_key: false,
+            node_id,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::static_kv::LabelBitmap;
+use crate::testing::lcg;
+use crate::testing::to_vecs;
+use crate::weighted_kv::WeightedKV;
+
+#[test]
+fn test_weighted_kv_example() -> anyhow::Result<()> {
+    let keys = to_vecs(&["car", "card", "care", "cart", "cat", "dog"]);
+    let weights = [5, 1, 9, 5, 7, 20];
+
+    let w = WeightedKV::<LabelBitmap>::new(&keys, &weights);
+
+    assert_eq!(Some(9), w.weight(b"care"));
+    assert_eq!(None, w.weight(b"ca"));
+    assert_eq!(None, w.weight(b"cow"));
+
+    let got = w.top_k(b"ca", 3);
+    assert_eq!(
+        vec![
+            (b"care".to_vec(), 9),
+            (b"cat".to_vec(), 7),
+            (b"car".to_vec(), 5),
+        ],
+        got
+    );
+
+    let got = w.top_k(b"", 2);
+    assert_eq!(vec![(b"dog".to_vec(), 20), (b"care".to_vec(), 9)], got);
+
+    assert_eq!(6, w.top_k(b"", 100).len());
+    assert_eq!(0, w.top_k(b"x", 3).len());
+    assert_eq!(0, w.top_k(b"ca", 0).len());
+
+    Ok(())
+}
+
+#[test]
+fn test_weighted_kv() -> anyhow::Result<()> {
+    let mut rand = lcg(19);
+
+    let mut keys = (0..1000)
+        .map(|_| {
+            let len = 1 + rand(6);
+            (0..len).map(|_| b'a' + rand(4) as u8).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    let weights = keys.iter().map(|_| rand(50)).collect::<Vec<_>>();
+    let w = WeightedKV::<LabelBitmap>::new(&keys, &weights);
+
+    for prefix in ["", "a", "ab", "dd", "abc", "e"] {
+        for k in [1, 5, 50] {
+            let mut want = keys
+                .iter()
+                .zip(weights.iter())
+                .filter(|(key, _)| key.starts_with(prefix.as_bytes()))
+                .map(|(key, w)| (key.clone(), *w))
+                .collect::<Vec<_>>();
+            want.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            want.truncate(k);
+
+            assert_eq!(
+                want,
+                w.top_k(prefix.as_bytes(), k),
+                "prefix: {}, k: {}",
+                prefix,
+                k
+            );
+        }
+    }
+
+    Ok(())
+}